@@ -167,6 +167,18 @@ class ProveTurnDto implements ProveInputs {
   @Max(1)
   exitedFlag!: number;
 
+  @ApiPropertyOptional({
+    description: 'Turn public-input schema of the game (1 = default circuit, 2 = loot-mask-bound circuit)',
+    example: 1,
+    minimum: 1,
+    maximum: 2,
+  })
+  @IsOptional()
+  @IsInt()
+  @Min(1)
+  @Max(2)
+  piSchema?: number;
+
   // ── Optional: pre-computed commitments (for backend logging/validation) ────
 
   @ApiPropertyOptional({
@@ -42,6 +42,10 @@ export interface ProveInputs {
   noPathFlag: number;       // 0 or 1
   exitedFlag: number;       // 0 or 1 — player reached the exit cell this turn
 
+  // Turn public-input schema of the game: 1 (default) = turn_validity circuit,
+  // 2 = turn_validity_v2, which also binds the collected loot mask into pi_hash.
+  piSchema?: number;
+
   // Derived (computed by engine.ts) — included for logging/validation
   posCommitBefore?: string;  // hex 32 bytes
   posCommitAfter?: string;   // hex 32 bytes
@@ -57,6 +61,8 @@ export class ProofService {
   private readonly circuitDir: string;
   private readonly wasmPath: string;
   private readonly zkeyPath: string;
+  private readonly wasmPathV2: string;
+  private readonly zkeyPathV2: string;
 
   constructor() {
     // Resolve relative to the monorepo root
@@ -66,6 +72,18 @@ export class ProofService {
     );
     this.wasmPath  = path.join(this.circuitDir, 'turn_validity_js', 'turn_validity.wasm');
     this.zkeyPath  = path.join(this.circuitDir, 'turn_validity_final.zkey');
+    this.wasmPathV2 = path.join(this.circuitDir, 'turn_validity_v2_js', 'turn_validity_v2.wasm');
+    this.zkeyPathV2 = path.join(this.circuitDir, 'turn_validity_v2_final.zkey');
+  }
+
+  /** Circuit artefacts proving the given turn public-input schema. */
+  private artefactsFor(piSchema: number): { wasmPath: string; zkeyPath: string; suffix: string } {
+    switch (piSchema) {
+      case 1: return { wasmPath: this.wasmPath,   zkeyPath: this.zkeyPath,   suffix: '' };
+      case 2: return { wasmPath: this.wasmPathV2, zkeyPath: this.zkeyPathV2, suffix: ':v2' };
+      default:
+        throw new BadRequestException(`No circuit proves pi schema v${piSchema}.`);
+    }
   }
 
   // ── Helpers ────────────────────────────────────────────────────────────────
@@ -190,16 +208,17 @@ export class ProofService {
    * Proof generation time: ~1–5 seconds (vs 60–180s for UltraHonk WASM).
    */
   async generateProof(inputs: ProveInputs): Promise<string> {
-    if (!fs.existsSync(this.wasmPath)) {
+    const { wasmPath, zkeyPath, suffix } = this.artefactsFor(inputs.piSchema ?? 1);
+    if (!fs.existsSync(wasmPath)) {
       throw new BadRequestException(
-        `Circuit WASM not found at ${this.wasmPath}. ` +
-        `Run "npm run compile" inside apps/circuits/turn_validity_g16/ first.`,
+        `Circuit WASM not found at ${wasmPath}. ` +
+        `Run "npm run compile${suffix}" inside apps/circuits/turn_validity_g16/ first.`,
       );
     }
-    if (!fs.existsSync(this.zkeyPath)) {
+    if (!fs.existsSync(zkeyPath)) {
       throw new BadRequestException(
-        `Proving key not found at ${this.zkeyPath}. ` +
-        `Run "npm run setup" inside apps/circuits/turn_validity_g16/ first.`,
+        `Proving key not found at ${zkeyPath}. ` +
+        `Run "npm run setup${suffix}" inside apps/circuits/turn_validity_g16/ first.`,
       );
     }
 
@@ -210,8 +229,8 @@ export class ProofService {
 
     const { proof, publicSignals } = await snarkjs.groth16.fullProve(
       circuitInputs,
-      this.wasmPath,
-      this.zkeyPath,
+      wasmPath,
+      zkeyPath,
     );
 
     const elapsed = ((Date.now() - t0) / 1000).toFixed(1);
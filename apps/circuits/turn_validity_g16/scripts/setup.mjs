@@ -1,6 +1,11 @@
 /**
  * Groth16 trusted setup for turn_validity.circom
  *
+ * Usage: node scripts/setup.mjs [circuit]
+ *   circuit defaults to "turn_validity" (pi schema v1); pass "turn_validity_v2"
+ *   for the loot-mask-bound schema. Keys for a non-default circuit are written
+ *   as <circuit>_vk.json / <circuit>_vk.bin next to the default ones.
+ *
  * Generates the proving key (zkey) and verification key (vk) locally.
  * For testnet/hackathon use — the toxic waste is known (insecure), which is
  * acceptable for testing. For mainnet: use a multi-party ceremony.
@@ -16,9 +21,11 @@ const BUILD_DIR  = path.join(__dirname, "../build");
 const PTAU_0     = path.join(BUILD_DIR, "pot14_0000.ptau");
 const PTAU_1     = path.join(BUILD_DIR, "pot14_0001.ptau");
 const PTAU_FINAL = path.join(BUILD_DIR, "pot14_final.ptau");
-const R1CS_FILE  = path.join(BUILD_DIR, "turn_validity.r1cs");
-const ZKEY_0     = path.join(BUILD_DIR, "turn_validity_0000.zkey");
-const ZKEY_FINAL = path.join(BUILD_DIR, "turn_validity_final.zkey");
+const CIRCUIT    = process.argv[2] ?? "turn_validity";
+const VK_PREFIX  = CIRCUIT === "turn_validity" ? "" : `${CIRCUIT}_`;
+const R1CS_FILE  = path.join(BUILD_DIR, `${CIRCUIT}.r1cs`);
+const ZKEY_0     = path.join(BUILD_DIR, `${CIRCUIT}_0000.zkey`);
+const ZKEY_FINAL = path.join(BUILD_DIR, `${CIRCUIT}_final.zkey`);
 
 mkdirSync(BUILD_DIR, { recursive: true });
 
@@ -52,20 +59,20 @@ async function main() {
     "HeistDuel Circuit Testnet",
     "circuit-contribution-testnet-entropy",
   );
-  console.log("✓  turn_validity_final.zkey ready");
+  console.log(`✓  ${CIRCUIT}_final.zkey ready`);
 
   // ── Export VK as JSON ──────────────────────────────────────────────────────
   console.log("\n⚙  Exporting verification key...");
   const vkJson = await snarkjs.zKey.exportVerificationKey(ZKEY_FINAL);
-  const vkJsonPath = path.join(BUILD_DIR, "vk.json");
+  const vkJsonPath = path.join(BUILD_DIR, `${VK_PREFIX}vk.json`);
   writeFileSync(vkJsonPath, JSON.stringify(vkJson, null, 2));
-  console.log("✓  vk.json exported");
+  console.log(`✓  ${VK_PREFIX}vk.json exported`);
 
   // ── Export VK as binary for Soroban contract ───────────────────────────────
   const vkBin = exportVkBinary(vkJson);
-  const vkBinPath = path.join(BUILD_DIR, "vk.bin");
+  const vkBinPath = path.join(BUILD_DIR, `${VK_PREFIX}vk.bin`);
   writeFileSync(vkBinPath, vkBin);
-  console.log(`✓  vk.bin exported (${vkBin.length} bytes, Soroban format)`);
+  console.log(`✓  ${VK_PREFIX}vk.bin exported (${vkBin.length} bytes, Soroban format)`);
 
   console.log("\n✅ Setup complete!");
   console.log(`   Proving key:  ${ZKEY_FINAL}`);
@@ -0,0 +1,7 @@
+pragma circom 2.1.6;
+
+// HeistDuel turn validity circuit, schema v2: pi_hash = Poseidon3(h1, h2, loot_mask).
+
+include "turn_validity_core.circom";
+
+component main = TurnValidity(2);
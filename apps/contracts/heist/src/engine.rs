@@ -1,8 +1,47 @@
-use soroban_sdk::{crypto::BnScalar, vec, Bytes, BytesN, Env, U256};
+use soroban_sdk::{crypto::BnScalar, vec, Bytes, BytesN, Env, Vec, U256};
 use soroban_poseidon::poseidon_hash;
 
+use crate::{Game, GameConfig, TurnZkPublic};
+
+// ── Default rules (see GameConfig for per-session overrides) ──────────────────
 pub const PLAYER_TIME_SECONDS: u64 = 300; // 5 minutes per player (chess clock)
 pub const LOOT_COUNT: u32 = 24;
+// The turn circuit is hard-wired to a 12×12 grid; smaller boards fit inside it.
+pub const BOARD_SIDE: u32 = 12;
+// Loot cells live in an i128 mask whose sign bit is never a cell.
+pub const MAX_LOOT_CELLS: u32 = 127;
+// Window after start_game for both seed reveals and the begin_match co-signature.
+pub const REVEAL_TIMEOUT_SECONDS: u64 = 600;
+
+// ── Scoring ───────────────────────────────────────────────────────────────────
+// score_delta = loot_delta − CAMERA_PENALTY·camera_hits − LASER_PENALTY·laser_hits.
+// Map generation places at most 3 cameras and 2 lasers, and each hazard is
+// counted at most once per turn, which bounds the per-turn penalty.
+pub const CAMERA_PENALTY: i128 = 1;
+pub const LASER_PENALTY: i128 = 2;
+pub const MAX_CAMERA_HITS: u32 = 3;
+pub const MAX_LASER_HITS: u32 = 2;
+
+// ── Turn public-input schemas ─────────────────────────────────────────────────
+// A game pins the schema in force at begin_match; see compute_turn_pi_hash*.
+// v1: Poseidon2(h1, h2) — the original circuit; loot_mask only count-checked.
+// v2: v1 + loot_mask folded into pi_hash, score_delta client-computed (range-checked).
+// Schemas 3–5 (hazard counts, dice roll, map_commitment) were withdrawn before
+// any circuit proved them.
+// v6: v2's h1, h2 and loot_mask as three separate public inputs, without the
+//     final fold. No circuit proves it yet, so it is only selectable in builds
+//     with the `multi-input-schema` feature, which release builds leave off.
+pub const PI_SCHEMA_V1: u32 = 1;
+pub const PI_SCHEMA_V2: u32 = 2;
+pub const PI_SCHEMA_V6: u32 = 6;
+// Pinned when the admin hasn't selected a schema: the shipped circuit proves v1,
+// so deployments opt in to later schemas with set_pi_schema once they deploy
+// a circuit (and its VK) that proves them.
+pub const PI_SCHEMA_DEFAULT: u32 = PI_SCHEMA_V1;
+
+// ── Groth16 proof blob ────────────────────────────────────────────────────────
+// [4 n_pub][n_pub × 32 public inputs][64 pi_a][128 pi_b][64 pi_c]
+const PROOF_POINTS_LEN: u32 = 64 + 128 + 64;
 
 // ── BN254 Fr prime (big-endian) ───────────────────────────────────────────────
 // Used to negate field elements (−x ≡ prime − x mod prime).
@@ -87,35 +126,94 @@ pub fn compute_pos_commit(env: &Env, x: u32, y: u32, nonce: &BytesN<32>) -> Byte
     u256_to_bytes32(env, &h)
 }
 
-/// State commitment over all publicly committed on-chain values.
+/// State commitment over all publicly committed on-chain values of `game`.
 ///
 /// Kept as keccak256 (not in ZK circuit). The circuit no longer re-derives
 /// state commitments; the heist contract verifies them independently.
 /// deadline_ts has been removed — per-player chess clocks replace the global deadline.
-pub fn compute_state_commitment(
-    env: &Env,
-    session_id: u32,
-    turn_index: u32,
-    player1_score: i128,
-    player2_score: i128,
-    map_commitment: &BytesN<32>,
-    player1_pos_commit: &BytesN<32>,
-    player2_pos_commit: &BytesN<32>,
-    session_seed: &BytesN<32>,
-) -> BytesN<32> {
+pub fn compute_state_commitment(env: &Env, session_id: u32, game: &Game) -> BytesN<32> {
     let mut b = Bytes::new(env);
     b.append(&Bytes::from_array(env, &session_id.to_be_bytes()));
-    b.append(&Bytes::from_array(env, &turn_index.to_be_bytes()));
-    b.append(&Bytes::from_array(env, &player1_score.to_be_bytes()));
-    b.append(&Bytes::from_array(env, &player2_score.to_be_bytes()));
-    b.append(&Bytes::from(map_commitment.clone()));
-    b.append(&Bytes::from(player1_pos_commit.clone()));
-    b.append(&Bytes::from(player2_pos_commit.clone()));
-    b.append(&Bytes::from(session_seed.clone()));
+    b.append(&Bytes::from_array(env, &game.turn_index.to_be_bytes()));
+    b.append(&Bytes::from_array(env, &game.player1_score.to_be_bytes()));
+    b.append(&Bytes::from_array(env, &game.player2_score.to_be_bytes()));
+    b.append(&Bytes::from(game.map_commitment.clone()));
+    b.append(&Bytes::from(game.player1_pos_commit.clone()));
+    b.append(&Bytes::from(game.player2_pos_commit.clone()));
+    b.append(&Bytes::from(game.session_seed.clone()));
+    b.append(&Bytes::from(compute_config_hash(env, &game.config)));
     env.crypto().keccak256(&b).into()
 }
 
-/// Computes the single public-input hash for the Groth16 turn validity proof.
+/// Commitment to a game's rules, folded into every state commitment:
+/// keccak256(time_per_player ‖ clock_mode ‖ increment ‖ max_turns ‖ loot_count ‖ width ‖ height).
+pub fn compute_config_hash(env: &Env, config: &GameConfig) -> BytesN<32> {
+    let mut b = Bytes::new(env);
+    b.append(&Bytes::from_array(env, &config.time_per_player.to_be_bytes()));
+    b.append(&Bytes::from_array(env, &(config.clock_mode as u32).to_be_bytes()));
+    b.append(&Bytes::from_array(env, &config.increment_seconds.to_be_bytes()));
+    b.append(&Bytes::from_array(env, &config.max_turns.to_be_bytes()));
+    b.append(&Bytes::from_array(env, &config.loot_count.to_be_bytes()));
+    b.append(&Bytes::from_array(env, &config.board_width.to_be_bytes()));
+    b.append(&Bytes::from_array(env, &config.board_height.to_be_bytes()));
+    env.crypto().keccak256(&b).into()
+}
+
+pub fn pi_schema_supported(pi_schema: u32) -> bool {
+    match pi_schema {
+        PI_SCHEMA_V1 | PI_SCHEMA_V2 => true,
+        PI_SCHEMA_V6 => cfg!(feature = "multi-input-schema"),
+        _ => false,
+    }
+}
+
+/// Number of Groth16 public inputs a turn proof carries under `pi_schema`.
+pub fn pi_input_count(pi_schema: u32) -> u32 {
+    if pi_schema >= PI_SCHEMA_V6 { 3 } else { 1 }
+}
+
+/// Length of a proof blob carrying `n_inputs` public inputs.
+pub fn proof_blob_len(n_inputs: u32) -> u32 {
+    4 + n_inputs * 32 + PROOF_POINTS_LEN
+}
+
+/// Net score change for a turn given its loot and hazard counts.
+pub fn hazard_score_delta(loot_delta: u32, camera_hits: u32, laser_hits: u32) -> i128 {
+    loot_delta as i128 - CAMERA_PENALTY * camera_hits as i128 - LASER_PENALTY * laser_hits as i128
+}
+
+/// Whether `score_delta` is reachable from `loot_delta` under any hazard exposure:
+/// never more than the loot collected, never less than the worst-case penalty.
+pub fn score_delta_in_range(loot_delta: u32, score_delta: i128) -> bool {
+    score_delta <= loot_delta as i128
+        && score_delta >= hazard_score_delta(loot_delta, MAX_CAMERA_HITS, MAX_LASER_HITS)
+}
+
+// h1 = Poseidon4(session_id, turn_index, player_tag, pos_commit_before)
+fn turn_h1(env: &Env, turn: &TurnZkPublic, player_tag: u32) -> U256 {
+    poseidon_hash::<5, BnScalar>(env, &vec![
+        env,
+        u32_to_u256(env, turn.session_id),
+        u32_to_u256(env, turn.turn_index),
+        u32_to_u256(env, player_tag),
+        bytes32_to_u256(env, &turn.pos_commit_before),
+    ])
+}
+
+// h2 = Poseidon5(pos_commit_after, score_delta_fr, loot_delta, no_path_flag, exited_flag)
+fn turn_h2(env: &Env, turn: &TurnZkPublic) -> U256 {
+    poseidon_hash::<6, BnScalar>(env, &vec![
+        env,
+        bytes32_to_u256(env, &turn.pos_commit_after),
+        i128_to_u256(env, turn.score_delta),
+        u32_to_u256(env, turn.loot_delta),
+        u32_to_u256(env, if turn.no_path_flag { 1 } else { 0 }),
+        u32_to_u256(env, if turn.exited_flag { 1 } else { 0 }),
+    ])
+}
+
+/// Computes the single public-input hash for the Groth16 turn validity proof
+/// (schema v1).
 ///
 /// Formula (matches the Circom circuit exactly):
 ///   h1       = Poseidon4(session_id, turn_index, player_tag, pos_commit_before)
@@ -123,38 +221,47 @@ pub fn compute_state_commitment(
 ///   pi_hash  = Poseidon2(h1, h2)
 ///
 /// score_delta uses BN254 Fr representation: negative values → prime + value.
-pub fn compute_turn_pi_hash(
-    env: &Env,
-    session_id: u32,
-    turn_index: u32,
-    player_tag: u32,
-    pos_commit_before: &BytesN<32>,
-    pos_commit_after: &BytesN<32>,
-    score_delta: i128,
-    loot_delta: u32,
-    no_path_flag: bool,
-    exited_flag: bool,
-) -> BytesN<32> {
-    // h1 = Poseidon4(session_id, turn_index, player_tag, pos_commit_before)
-    let h1 = poseidon_hash::<5, BnScalar>(env, &vec![
-        env,
-        u32_to_u256(env, session_id),
-        u32_to_u256(env, turn_index),
-        u32_to_u256(env, player_tag),
-        bytes32_to_u256(env, pos_commit_before),
-    ]);
-
-    // h2 = Poseidon5(pos_commit_after, score_delta_fr, loot_delta, no_path_flag, exited_flag)
-    let h2 = poseidon_hash::<6, BnScalar>(env, &vec![
-        env,
-        bytes32_to_u256(env, pos_commit_after),
-        i128_to_u256(env, score_delta),
-        u32_to_u256(env, loot_delta),
-        u32_to_u256(env, if no_path_flag { 1 } else { 0 }),
-        u32_to_u256(env, if exited_flag { 1 } else { 0 }),
-    ]);
+pub fn compute_turn_pi_hash(env: &Env, turn: &TurnZkPublic, player_tag: u32) -> BytesN<32> {
+    let h1 = turn_h1(env, turn, player_tag);
+    let h2 = turn_h2(env, turn);
 
     // pi_hash = Poseidon2(h1, h2)
     let pi = poseidon_hash::<3, BnScalar>(env, &vec![env, h1, h2]);
     u256_to_bytes32(env, &pi)
 }
+
+/// Schema v2 public-input hash: v1 plus the per-turn loot mask, so the proof
+/// binds the exact cells collected, not just their count.
+///
+/// Formula (matches the Circom circuit built with `TurnValidity(2)`):
+///   h1       = Poseidon4(session_id, turn_index, player_tag, pos_commit_before)
+///   h2       = Poseidon5(pos_commit_after, score_delta_fr, loot_delta, no_path_flag, exited_flag)
+///   pi_hash  = Poseidon3(h1, h2, loot_mask)
+///
+/// loot_mask is the per-turn collected-cells bitmask (cells 0-126, always >= 0),
+/// so it fits in a single Fr element.
+pub fn compute_turn_pi_hash_v2(env: &Env, turn: &TurnZkPublic, player_tag: u32) -> BytesN<32> {
+    let h1 = turn_h1(env, turn, player_tag);
+    let h2 = turn_h2(env, turn);
+
+    // pi_hash = Poseidon3(h1, h2, loot_mask)
+    let pi = poseidon_hash::<4, BnScalar>(env, &vec![env, h1, h2, i128_to_u256(env, turn.loot_mask)]);
+    u256_to_bytes32(env, &pi)
+}
+
+/// Schema v6 public inputs: v2's h1, h2 and loot_mask in that order, each its
+/// own Groth16 public input rather than folded into a single pi_hash.
+pub fn compute_turn_public_inputs_v6(
+    env: &Env,
+    turn: &TurnZkPublic,
+    player_tag: u32,
+) -> Vec<BytesN<32>> {
+    let h1 = turn_h1(env, turn, player_tag);
+    let h2 = turn_h2(env, turn);
+    vec![
+        env,
+        u256_to_bytes32(env, &h1),
+        u256_to_bytes32(env, &h2),
+        u256_to_bytes32(env, &i128_to_u256(env, turn.loot_mask)),
+    ]
+}
@@ -0,0 +1,165 @@
+//! Typed contract events.
+//!
+//! Every state transition publishes one of these, with the session id as a
+//! topic where there is one, so an indexer can rebuild each game purely from
+//! the event stream. The first topic is the struct name in snake case.
+
+use soroban_sdk::{contractevent, Address, BytesN};
+
+use crate::{GameConfig, GameOutcome};
+
+// ── Game lifecycle ────────────────────────────────────────────────────────────
+
+#[contractevent]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameCreated {
+    #[topic]
+    pub session_id: u32,
+    pub player1: Address,
+    pub player2: Address,
+    pub player1_points: i128,
+    pub player2_points: i128,
+    pub config: GameConfig,
+    pub reveal_deadline_ts: u64,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SeedRevealed {
+    #[topic]
+    pub session_id: u32,
+    pub player: Address,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, PartialEq)]
+pub struct MatchBegun {
+    #[topic]
+    pub session_id: u32,
+    pub map_commitment: BytesN<32>,
+    pub session_seed: BytesN<32>,
+    pub p1_pos_commit: BytesN<32>,
+    pub p2_pos_commit: BytesN<32>,
+    pub state_commitment: BytesN<32>,
+    pub pi_schema: u32,
+    pub vk_id: Option<BytesN<32>>,
+    pub started_at_ts: u64,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TurnAccepted {
+    #[topic]
+    pub session_id: u32,
+    pub turn_index: u32,
+    pub player: Address,
+    pub score_delta: i128,
+    pub loot_delta: u32,
+    pub loot_mask: i128,
+    pub exited_flag: bool,
+    pub pos_commit_after: BytesN<32>,
+    pub state_commitment: BytesN<32>,
+    pub proof_id: BytesN<32>,
+    pub p1_time_remaining: u64,
+    pub p2_time_remaining: u64,
+    // Player to move next, after any auto-skip of an exited player.
+    pub active_player: Address,
+}
+
+/// An exited player's turn was skipped via pass_turn.
+#[contractevent]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TurnPassed {
+    #[topic]
+    pub session_id: u32,
+    pub active_player: Address,
+    pub last_turn_start_ts: u64,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClockExpired {
+    #[topic]
+    pub session_id: u32,
+    pub player: Address,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, PartialEq)]
+pub struct DrawOffered {
+    #[topic]
+    pub session_id: u32,
+    pub player: Address,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PiSchemaMigrated {
+    #[topic]
+    pub session_id: u32,
+    pub pi_schema: u32,
+    pub vk_id: Option<BytesN<32>>,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameEnded {
+    #[topic]
+    pub session_id: u32,
+    pub player1_score: i128,
+    pub player2_score: i128,
+    pub winner: Option<Address>,
+    pub outcome: GameOutcome,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResultNotReported {
+    #[topic]
+    pub session_id: u32,
+    pub outcome: GameOutcome,
+}
+
+// ── Admin ─────────────────────────────────────────────────────────────────────
+
+#[contractevent]
+#[derive(Clone, Debug, PartialEq)]
+pub struct AdminChanged {
+    pub admin: Address,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, PartialEq)]
+pub struct HubChanged {
+    pub hub: Address,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, PartialEq)]
+pub struct VerifierChanged {
+    pub verifier: Address,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PiSchemaSet {
+    pub pi_schema: u32,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, PartialEq)]
+pub struct StakeTokenSet {
+    pub token: Option<Address>,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeeBpsSet {
+    pub fee_bps: u32,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Upgraded {
+    pub new_wasm_hash: BytesN<32>,
+}
@@ -1,18 +1,35 @@
 #![no_std]
 
 mod engine;
+mod events;
 
 use soroban_sdk::{
-    contract, contractclient, contracterror, contractimpl, contracttype, symbol_short,
-    vec, Address, Bytes, BytesN, Env, IntoVal,
+    contract, contractclient, contracterror, contractimpl, contracttype, token, vec, Address, Bytes, BytesN, Env, IntoVal, Vec,
 };
 
 use engine::{
     commit_hash, compute_state_commitment, compute_turn_pi_hash,
-    derive_session_seed, roll_value, PLAYER_TIME_SECONDS,
+    compute_turn_pi_hash_v2, compute_turn_public_inputs_v6, derive_session_seed,
+    pi_input_count, pi_schema_supported, proof_blob_len, roll_value, score_delta_in_range,
+    BOARD_SIDE, MAX_LOOT_CELLS, PI_SCHEMA_DEFAULT, PI_SCHEMA_V1, PI_SCHEMA_V2, PI_SCHEMA_V6,
+    LOOT_COUNT, PLAYER_TIME_SECONDS, REVEAL_TIMEOUT_SECONDS,
+};
+use events::{
+    AdminChanged, ClockExpired, DrawOffered, FeeBpsSet, GameCreated, GameEnded, HubChanged,
+    MatchBegun, PiSchemaMigrated, PiSchemaSet, ResultNotReported, SeedRevealed, StakeTokenSet,
+    TurnAccepted, TurnPassed, Upgraded, VerifierChanged,
 };
 
 const GAME_TTL_LEDGERS: u32 = 518_400;
+// Upper bound on get_turns page size.
+const MAX_TURNS_PAGE: u32 = 50;
+// ~180 days at 5s ledgers; archived results can still be restored after that.
+const MATCH_RESULT_TTL_LEDGERS: u32 = 3_110_400;
+// Escrow fee is expressed in basis points of the combined stake.
+const MAX_FEE_BPS: u32 = 10_000;
+// Session ids with this bit set are handed out by create_game only;
+// start_game rejects them, so caller-chosen ids can never collide.
+const ALLOCATED_SESSION_ID_BIT: u32 = 1 << 31;
 
 #[contractclient(name = "GameHubClient")]
 pub trait GameHub {
@@ -32,13 +49,22 @@ pub trait GameHub {
 /// Matches the actual ultrahonk_soroban_contract interface.
 /// The single public input (pi_hash) is embedded inside proof_blob;
 /// the contract verifies it matches TurnZkPublic before calling this.
+/// Verifier failures come back as contract errors (zk-verifier's `VerifierError`).
+/// `consume_proof` only accepts this contract once the verifier's admin has
+/// registered it with `set_consumer`.
 #[contractclient(name = "ZkVerifierClient")]
 pub trait ZkVerifier {
-    fn verify_proof_with_stored_vk(env: Env, proof_blob: Bytes) -> BytesN<32>;
+    fn consume_proof(
+        env: Env,
+        consumer: Address,
+        vk_id: Option<BytesN<32>>,
+        proof_blob: Bytes,
+    ) -> Result<BytesN<32>, soroban_sdk::Error>;
+    fn get_vk_hash(env: Env) -> Option<BytesN<32>>;
 }
 
 #[contracterror]
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
 #[repr(u32)]
 pub enum Error {
     GameNotFound = 1,
@@ -57,6 +83,15 @@ pub enum Error {
     ProofRequired = 18,
     InvalidPublicInput = 19,
     PlayerAlreadyExited = 20,
+    UnsupportedPiSchema = 21,
+    RevealWindowOpen = 22,
+    InvalidStake = 23,
+    InvalidFee = 24,
+    NoDrawOffer = 25,
+    InvalidConfig = 26,
+    ProofInvalid = 27,
+    ReservedSessionId = 28,
+    SessionIdsExhausted = 29,
 }
 
 #[contracttype]
@@ -67,6 +102,63 @@ pub enum GameStatus {
     Ended,
 }
 
+/// Why a game ended.
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EndReason {
+    // Both players reached the exit.
+    BothExited,
+    // A chess clock ran out.
+    Timeout,
+    // Only one player revealed their seed before the reveal deadline.
+    RevealTimeout,
+    // A player conceded via resign.
+    Resignation,
+    // Both players agreed to a draw.
+    Agreement,
+    // GameConfig::max_turns turns were played; adjudicated on score.
+    TurnLimit,
+}
+
+/// Final result of a game, recorded on `Game` and carried in the `GameEnded` event.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum GameOutcome {
+    // The game has not ended yet.
+    Pending,
+    P1Win(EndReason),
+    P2Win(EndReason),
+    Draw(EndReason),
+}
+
+/// How `GameConfig::increment_seconds` is credited after an accepted turn.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum ClockMode {
+    // The full increment, however long the turn took.
+    Fischer = 0,
+    // The time actually used, capped at the increment (Bronstein delay).
+    Bronstein = 1,
+}
+
+/// Per-session rules, chosen at start_game and signed by both players.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameConfig {
+    // Chess clock budget per player, in seconds.
+    pub time_per_player: u64,
+    pub clock_mode: ClockMode,
+    // Credited to a player's clock after each accepted turn, per clock_mode.
+    pub increment_seconds: u64,
+    // 0 = no turn limit.
+    pub max_turns: u32,
+    pub loot_count: u32,
+    // At most BOARD_SIDE in each direction (the circuit's grid).
+    pub board_width: u32,
+    pub board_height: u32,
+}
+
 #[contracttype]
 #[derive(Clone)]
 pub struct TurnZkPublic {
@@ -78,6 +170,7 @@ pub struct TurnZkPublic {
     // Bitmask of loot cells collected this turn (cells 0-126, flat index y*12+x).
     // Bit N set means cell N was collected. count_ones() must equal loot_delta.
     // Must not overlap with game.loot_mask (prevents double-collecting).
+    // Bound into pi_hash from schema v2, so the proof attests to these exact cells.
     // Stored as i128; cell indices limited to 0-126 so value is always >= 0.
     pub loot_mask: i128,
     pub pos_commit_before: BytesN<32>,
@@ -129,6 +222,144 @@ pub struct Game {
     // u64::MAX = not yet exited; otherwise the turn_index when this player exited.
     pub p1_exit_turn: u64,
     pub p2_exit_turn: u64,
+    // Turn public-input schema pinned at begin_match. 0 = not yet pinned.
+    pub pi_schema: u32,
+    // Verifier key pinned at begin_match (the verifier's default at the time).
+    // None = verify against whatever the verifier's default is.
+    pub vk_id: Option<BytesN<32>>,
+    // After this timestamp a game still in WaitingReveal can be closed via
+    // claim_reveal_timeout.
+    pub reveal_deadline_ts: u64,
+    // Token holding both players' points in escrow. None = no escrow.
+    pub stake_token: Option<Address>,
+    // Admin fee taken from the pot at payout, pinned at start_game.
+    pub fee_bps: u32,
+    // Pending draw offer; lapses once the other player submits a turn.
+    pub draw_offered_by: Option<Address>,
+    pub outcome: GameOutcome,
+    pub config: GameConfig,
+}
+
+/// `Game` as stored by the first release of this contract, before schemas,
+/// escrow and per-game config. Only read back from `DataKey::Game` so games
+/// in flight across the upgrade keep going; see `Game::from_legacy`.
+#[contracttype]
+#[derive(Clone)]
+pub struct LegacyGame {
+    pub player1: Address,
+    pub player2: Address,
+    pub player1_points: i128,
+    pub player2_points: i128,
+    pub status: GameStatus,
+    pub p1_seed_commit: BytesN<32>,
+    pub p2_seed_commit: BytesN<32>,
+    pub p1_seed_reveal: BytesN<32>,
+    pub p2_seed_reveal: BytesN<32>,
+    pub session_seed: BytesN<32>,
+    pub p1_map_seed_commit: BytesN<32>,
+    pub p2_map_seed_commit: BytesN<32>,
+    pub map_commitment: BytesN<32>,
+    pub player1_pos_commit: BytesN<32>,
+    pub player2_pos_commit: BytesN<32>,
+    pub player1_score: i128,
+    pub player2_score: i128,
+    pub loot_total_collected: u32,
+    pub loot_mask: i128,
+    pub state_commitment: BytesN<32>,
+    pub started_at_ts: u64,
+    pub turn_index: u32,
+    pub active_player: Address,
+    pub winner: Option<Address>,
+    pub last_proof_id: BytesN<32>,
+    pub p1_time_remaining: u64,
+    pub p2_time_remaining: u64,
+    pub last_turn_start_ts: u64,
+    pub player1_exited: bool,
+    pub player2_exited: bool,
+    pub p1_exit_turn: u64,
+    pub p2_exit_turn: u64,
+}
+
+impl Game {
+    /// Carries a first-release game over to the current layout. The old
+    /// contract had no escrow, fixed rules and only the v1 circuit, so a
+    /// started game is pinned to schema v1 and every new field takes the value
+    /// that reproduces the old behaviour. A game still waiting on reveals gets
+    /// a fresh reveal window, since the old one had no deadline at all.
+    fn from_legacy(env: &Env, legacy: LegacyGame) -> Game {
+        let started = legacy.status != GameStatus::WaitingReveal;
+        let outcome = if legacy.status != GameStatus::Ended {
+            GameOutcome::Pending
+        } else {
+            // The first release only ended games on both exits or a clock timeout.
+            let reason = if legacy.player1_exited && legacy.player2_exited {
+                EndReason::BothExited
+            } else {
+                EndReason::Timeout
+            };
+            match &legacy.winner {
+                Some(w) if *w == legacy.player1 => GameOutcome::P1Win(reason),
+                Some(_) => GameOutcome::P2Win(reason),
+                None => GameOutcome::Draw(reason),
+            }
+        };
+        Game {
+            player1: legacy.player1,
+            player2: legacy.player2,
+            player1_points: legacy.player1_points,
+            player2_points: legacy.player2_points,
+            status: legacy.status,
+            p1_seed_commit: legacy.p1_seed_commit,
+            p2_seed_commit: legacy.p2_seed_commit,
+            p1_seed_reveal: legacy.p1_seed_reveal,
+            p2_seed_reveal: legacy.p2_seed_reveal,
+            session_seed: legacy.session_seed,
+            p1_map_seed_commit: legacy.p1_map_seed_commit,
+            p2_map_seed_commit: legacy.p2_map_seed_commit,
+            map_commitment: legacy.map_commitment,
+            player1_pos_commit: legacy.player1_pos_commit,
+            player2_pos_commit: legacy.player2_pos_commit,
+            player1_score: legacy.player1_score,
+            player2_score: legacy.player2_score,
+            loot_total_collected: legacy.loot_total_collected,
+            loot_mask: legacy.loot_mask,
+            state_commitment: legacy.state_commitment,
+            started_at_ts: legacy.started_at_ts,
+            turn_index: legacy.turn_index,
+            active_player: legacy.active_player,
+            winner: legacy.winner,
+            last_proof_id: legacy.last_proof_id,
+            p1_time_remaining: legacy.p1_time_remaining,
+            p2_time_remaining: legacy.p2_time_remaining,
+            last_turn_start_ts: legacy.last_turn_start_ts,
+            player1_exited: legacy.player1_exited,
+            player2_exited: legacy.player2_exited,
+            p1_exit_turn: legacy.p1_exit_turn,
+            p2_exit_turn: legacy.p2_exit_turn,
+            pi_schema: if started { PI_SCHEMA_V1 } else { 0 },
+            // Pin the key in force now, as begin_match would have, so a later
+            // default-key change doesn't strand the game.
+            vk_id: if started { HeistContract::current_vk_id(env) } else { None },
+            reveal_deadline_ts: if started {
+                0
+            } else {
+                env.ledger().timestamp() + REVEAL_TIMEOUT_SECONDS
+            },
+            stake_token: None,
+            fee_bps: 0,
+            draw_offered_by: None,
+            outcome,
+            config: GameConfig {
+                time_per_player: PLAYER_TIME_SECONDS,
+                clock_mode: ClockMode::Fischer,
+                increment_seconds: 0,
+                max_turns: 0,
+                loot_count: LOOT_COUNT,
+                board_width: BOARD_SIDE,
+                board_height: BOARD_SIDE,
+            },
+        }
+    }
 }
 
 #[contracttype]
@@ -159,6 +390,63 @@ pub struct GameView {
     pub last_turn_start_ts: u64,
     pub player1_exited: bool,
     pub player2_exited: bool,
+    pub pi_schema: u32,
+    pub vk_id: Option<BytesN<32>>,
+    pub reveal_deadline_ts: u64,
+    pub stake_token: Option<Address>,
+    pub draw_offered_by: Option<Address>,
+    pub outcome: GameOutcome,
+    pub config: GameConfig,
+}
+
+/// One accepted turn, kept alongside the game for audits and replays.
+#[contracttype]
+#[derive(Clone)]
+pub struct TurnRecord {
+    pub turn_index: u32,
+    // 1 = player1, 2 = player2.
+    pub player_tag: u32,
+    pub pos_commit_after: BytesN<32>,
+    pub score_delta: i128,
+    pub loot_mask: i128,
+    pub proof_id: BytesN<32>,
+    // Ledger timestamp at which the turn was accepted.
+    pub timestamp: u64,
+    // Seconds charged to the player's clock for this turn.
+    pub elapsed: u64,
+}
+
+/// A game as seen by one of its players, with clocks charged up to the current
+/// ledger timestamp.
+#[contracttype]
+#[derive(Clone)]
+pub struct PlayerView {
+    pub game: GameView,
+    pub opponent: Address,
+    // 1 = player1, 2 = player2.
+    pub player_tag: u32,
+    pub is_my_turn: bool,
+    pub my_time_remaining: u64,
+    pub opponent_time_remaining: u64,
+}
+
+/// Compact, persistent record of a finished game. Outlives the temporary
+/// `Game` entry so results can be re-checked from chain state later.
+#[contracttype]
+#[derive(Clone)]
+pub struct MatchResult {
+    pub player1: Address,
+    pub player2: Address,
+    pub player1_score: i128,
+    pub player2_score: i128,
+    pub winner: Option<Address>,
+    pub outcome: GameOutcome,
+    // Number of accepted turns.
+    pub turn_count: u32,
+    pub final_state_commitment: BytesN<32>,
+    // Zero hash = no proof was ever submitted.
+    pub last_proof_id: BytesN<32>,
+    pub ended_at_ts: u64,
 }
 
 // ── Loot mask helpers (i128 bitmask, cells 0-126) ─────────────────────────────
@@ -173,13 +461,41 @@ fn loot_bits_overlap(a: i128, b: i128) -> bool {
     (a & b) != 0
 }
 
+fn game_config_valid(config: &GameConfig) -> bool {
+    // Range-check the sides before multiplying them, so an oversized board is
+    // rejected instead of overflowing.
+    if !(1..=BOARD_SIDE).contains(&config.board_width)
+        || !(1..=BOARD_SIDE).contains(&config.board_height)
+    {
+        return false;
+    }
+    let cells = config.board_width * config.board_height;
+    config.time_per_player > 0 && config.loot_count <= cells.min(MAX_LOOT_CELLS)
+}
+
+/// Time credited back to a player's clock once their turn has been accepted.
+fn clock_credit(config: &GameConfig, elapsed: u64) -> u64 {
+    match config.clock_mode {
+        ClockMode::Fischer => config.increment_seconds,
+        ClockMode::Bronstein => config.increment_seconds.min(elapsed),
+    }
+}
+
 #[contracttype]
 #[derive(Clone)]
 pub enum DataKey {
+    // First-release game layout (LegacyGame), moved to GameV2 on first access.
     Game(u32),
+    GameV2(u32),
     Admin,
     GameHubAddress,
     VerifierAddress,
+    PiSchema,
+    StakeToken,
+    FeeBps,
+    MatchResult(u32),
+    NextSessionId,
+    TurnRecord(u32, u32),
 }
 
 #[contract]
@@ -208,11 +524,15 @@ impl HeistContract {
         p2_seed_commit: BytesN<32>,
         p1_map_seed_commit: BytesN<32>,
         p2_map_seed_commit: BytesN<32>,
+        config: GameConfig,
     ) -> Result<(), Error> {
         if player1 == player2 {
             panic!("Cannot play against yourself");
         }
-        if Self::load_game(&env, session_id).is_some() {
+        if session_id & ALLOCATED_SESSION_ID_BIT != 0 {
+            return Err(Error::ReservedSessionId);
+        }
+        if Self::session_id_taken(&env, session_id) {
             return Err(Error::GameAlreadyStarted);
         }
 
@@ -222,6 +542,7 @@ impl HeistContract {
             player1_points.into_val(&env),
             p1_seed_commit.clone().into_val(&env),
             p1_map_seed_commit.clone().into_val(&env),
+            config.clone().into_val(&env),
         ]);
         player2.require_auth_for_args(vec![
             &env,
@@ -229,60 +550,85 @@ impl HeistContract {
             player2_points.into_val(&env),
             p2_seed_commit.clone().into_val(&env),
             p2_map_seed_commit.clone().into_val(&env),
+            config.clone().into_val(&env),
         ]);
 
-        // Notify hub of the new session. Non-fatal — game proceeds even if hub is
-        // unavailable (e.g. during isolated testnet deployments).
-        if let Some(hub_addr) = env.storage().instance().get::<_, Address>(&DataKey::GameHubAddress) {
-            let hub = GameHubClient::new(&env, &hub_addr);
-            let _ = hub.try_start_game(
-                &env.current_contract_address(),
-                &session_id,
-                &player1,
-                &player2,
-                &player1_points,
-                &player2_points,
-            );
+        Self::open_game(
+            &env,
+            session_id,
+            player1,
+            player2,
+            player1_points,
+            player2_points,
+            p1_seed_commit,
+            p2_seed_commit,
+            p1_map_seed_commit,
+            p2_map_seed_commit,
+            config,
+        )
+    }
+
+    /// Same as start_game, but the contract allocates the session id and returns
+    /// it. Ids are allocated in order from the range with the high bit set,
+    /// which start_game rejects, so they never collide with caller-chosen ids.
+    /// Players sign the same arguments as for start_game, minus the session id.
+    pub fn create_game(
+        env: Env,
+        player1: Address,
+        player2: Address,
+        player1_points: i128,
+        player2_points: i128,
+        p1_seed_commit: BytesN<32>,
+        p2_seed_commit: BytesN<32>,
+        p1_map_seed_commit: BytesN<32>,
+        p2_map_seed_commit: BytesN<32>,
+        config: GameConfig,
+    ) -> Result<u32, Error> {
+        if player1 == player2 {
+            panic!("Cannot play against yourself");
         }
 
-        let zero32 = BytesN::from_array(&env, &[0u8; 32]);
-        let game = Game {
-            player1: player1.clone(),
-            player2: player2.clone(),
+        player1.require_auth_for_args(vec![
+            &env,
+            player1_points.into_val(&env),
+            p1_seed_commit.clone().into_val(&env),
+            p1_map_seed_commit.clone().into_val(&env),
+            config.clone().into_val(&env),
+        ]);
+        player2.require_auth_for_args(vec![
+            &env,
+            player2_points.into_val(&env),
+            p2_seed_commit.clone().into_val(&env),
+            p2_map_seed_commit.clone().into_val(&env),
+            config.clone().into_val(&env),
+        ]);
+
+        // Index of the next id within the reserved range.
+        let index: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::NextSessionId)
+            .unwrap_or(0);
+        if index >= ALLOCATED_SESSION_ID_BIT {
+            return Err(Error::SessionIdsExhausted);
+        }
+        let session_id = ALLOCATED_SESSION_ID_BIT | index;
+        env.storage().instance().set(&DataKey::NextSessionId, &(index + 1));
+
+        Self::open_game(
+            &env,
+            session_id,
+            player1,
+            player2,
             player1_points,
             player2_points,
-            status: GameStatus::WaitingReveal,
             p1_seed_commit,
             p2_seed_commit,
-            p1_seed_reveal: zero32.clone(),
-            p2_seed_reveal: zero32.clone(),
-            session_seed: zero32.clone(),
             p1_map_seed_commit,
             p2_map_seed_commit,
-            map_commitment: zero32.clone(),
-            player1_pos_commit: zero32.clone(),
-            player2_pos_commit: zero32.clone(),
-            player1_score: 0,
-            player2_score: 0,
-            loot_total_collected: 0,
-            loot_mask: 0i128,
-            state_commitment: zero32.clone(),
-            started_at_ts: 0,
-            turn_index: 0,
-            active_player: player1.clone(),
-            winner: None,
-            last_proof_id: zero32,
-            p1_time_remaining: PLAYER_TIME_SECONDS,
-            p2_time_remaining: PLAYER_TIME_SECONDS,
-            last_turn_start_ts: 0,
-            player1_exited: false,
-            player2_exited: false,
-            p1_exit_turn: u64::MAX,
-            p2_exit_turn: u64::MAX,
-        };
-
-        Self::save_game(&env, session_id, &game);
-        Ok(())
+            config,
+        )?;
+        Ok(session_id)
     }
 
     pub fn reveal_seed(
@@ -322,6 +668,7 @@ impl HeistContract {
         }
 
         Self::save_game(&env, session_id, &game);
+        SeedRevealed { session_id, player }.publish(&env);
         Ok(())
     }
 
@@ -349,35 +696,44 @@ impl HeistContract {
         let session_seed = derive_session_seed(&env, session_id, &p1, &p2);
         let now = env.ledger().timestamp();
 
-        // Compute initial state commitment without deadline (chess clocks replace global timer).
-        let state_commitment = compute_state_commitment(
-            &env,
-            session_id,
-            0, // turn_index starts at 0
-            0, // player1_score starts at 0
-            0, // player2_score starts at 0
-            &map_commitment,
-            &p1_pos_commit,
-            &p2_pos_commit,
-            &session_seed,
-        );
-
         game.session_seed = session_seed;
         game.map_commitment = map_commitment;
         game.player1_pos_commit = p1_pos_commit;
         game.player2_pos_commit = p2_pos_commit;
         game.loot_total_collected = 0;
-        game.state_commitment = state_commitment;
         game.started_at_ts = now;
         game.status = GameStatus::Active;
         game.active_player = game.player1.clone();
         game.turn_index = 0;
+        // Initial state commitment, without a deadline (chess clocks replace the global timer).
+        game.state_commitment = compute_state_commitment(&env, session_id, &game);
         // Initialize per-player chess clocks.
-        game.p1_time_remaining = PLAYER_TIME_SECONDS;
-        game.p2_time_remaining = PLAYER_TIME_SECONDS;
+        game.p1_time_remaining = game.config.time_per_player;
+        game.p2_time_remaining = game.config.time_per_player;
         game.last_turn_start_ts = now;
+        // Pin the public-input schema so later schema rollouts don't break this game.
+        game.pi_schema = env
+            .storage()
+            .instance()
+            .get(&DataKey::PiSchema)
+            .unwrap_or(PI_SCHEMA_DEFAULT);
+        // Likewise the verifier key, so registering a new circuit's key doesn't
+        // break games already in flight.
+        game.vk_id = Self::current_vk_id(&env);
 
         Self::save_game(&env, session_id, &game);
+        MatchBegun {
+            session_id,
+            map_commitment: game.map_commitment,
+            session_seed: game.session_seed,
+            p1_pos_commit: game.player1_pos_commit,
+            p2_pos_commit: game.player2_pos_commit,
+            state_commitment: game.state_commitment,
+            pi_schema: game.pi_schema,
+            vk_id: game.vk_id,
+            started_at_ts: game.started_at_ts,
+        }
+        .publish(&env);
         Ok(())
     }
 
@@ -438,8 +794,11 @@ impl HeistContract {
             game.p2_time_remaining -= elapsed;
         }
 
-        // Groth16 proof blob: [4 n_pub=1][32 pi_hash][64 pi_a][128 pi_b][64 pi_c] = 292 bytes
-        if proof_blob.len() < 292 {
+        // Groth16 proof blob: [4 n_pub][n_pub × 32 inputs][64 pi_a][128 pi_b][64 pi_c],
+        // 292 bytes for the single-input schemas (v1/v2). The verifier refuses any
+        // other length, so don't pay for a call that can only fail.
+        let n_inputs = pi_input_count(game.pi_schema);
+        if proof_blob.len() != proof_blob_len(n_inputs) {
             return Err(Error::ProofRequired);
         }
 
@@ -458,46 +817,45 @@ impl HeistContract {
             return Err(Error::StateCommitMismatch);
         }
 
+        // The loot mask must be a valid cell bitmask (bit 127 is the i128 sign bit
+        // and never corresponds to a loot cell); from schema v2 it is also bound
+        // into pi_hash.
+        if public_turn.loot_mask < 0 {
+            return Err(Error::InvalidTurnData);
+        }
+
+        // A turn can never score more than the loot it collected, nor lose more
+        // than the worst-case hazard penalty.
+        if !score_delta_in_range(public_turn.loot_delta, public_turn.score_delta) {
+            return Err(Error::InvalidScoreDelta);
+        }
+
         let player_tag: u32 = if is_player1 { 1 } else { 2 };
 
-        // Compute the expected pi_hash from public turn data.
-        let expected_pi = compute_turn_pi_hash(
-            &env,
-            session_id,
-            game.turn_index,
-            player_tag,
-            &public_turn.pos_commit_before,
-            &public_turn.pos_commit_after,
-            public_turn.score_delta,
-            public_turn.loot_delta,
-            public_turn.no_path_flag,
-            public_turn.exited_flag,
-        );
+        // Compute the expected public inputs from public turn data under the
+        // game's schema: a single pi_hash for v1/v2, one input per hash for v6.
+        let expected_inputs: Vec<BytesN<32>> = match game.pi_schema {
+            PI_SCHEMA_V1 => vec![&env, compute_turn_pi_hash(&env, &public_turn, player_tag)],
+            PI_SCHEMA_V2 => vec![&env, compute_turn_pi_hash_v2(&env, &public_turn, player_tag)],
+            PI_SCHEMA_V6 => compute_turn_public_inputs_v6(&env, &public_turn, player_tag),
+            _ => return Err(Error::UnsupportedPiSchema),
+        };
 
-        // Verify proof_blob[0..4] = 0x00000001 (count = 1 public input).
+        // Verify proof_blob[0..4] = n_pub matches the schema's input count.
         let count = ((proof_blob.get(0).unwrap_or(0) as u32) << 24)
             | ((proof_blob.get(1).unwrap_or(0) as u32) << 16)
             | ((proof_blob.get(2).unwrap_or(0) as u32) << 8)
             | (proof_blob.get(3).unwrap_or(0) as u32);
-        if count != 1 {
+        if count != n_inputs {
             return Err(Error::InvalidPublicInput);
         }
 
-        // Verify proof_blob[4..36] matches the computed pi_hash.
-        let mut embedded_pi = [0u8; 32];
-        let mut k = 0u32;
-        while k < 32 {
-            embedded_pi[k as usize] = proof_blob.get(4 + k).unwrap_or(0);
-            k += 1;
-        }
-        let mut expected_pi_arr = [0u8; 32];
-        let mut k = 0u32;
-        while k < 32 {
-            expected_pi_arr[k as usize] = expected_pi.get(k).unwrap_or(0);
-            k += 1;
-        }
-        if embedded_pi != expected_pi_arr {
-            return Err(Error::InvalidPublicInput);
+        // Verify each embedded public input matches its on-chain value.
+        for (i, expected) in expected_inputs.iter().enumerate() {
+            let offset = 4 + i as u32 * 32;
+            if proof_blob.slice(offset..offset + 32) != Bytes::from(expected) {
+                return Err(Error::InvalidPublicInput);
+            }
         }
 
         // Call the ZK verifier.
@@ -507,7 +865,12 @@ impl HeistContract {
             .get(&DataKey::VerifierAddress)
             .expect("verifier missing");
         let verifier = ZkVerifierClient::new(&env, &verifier_addr);
-        let proof_id = verifier.verify_proof_with_stored_vk(&proof_blob);
+        let verified =
+            verifier.try_consume_proof(&env.current_contract_address(), &game.vk_id, &proof_blob);
+        let proof_id = match verified {
+            Ok(Ok(proof_id)) => proof_id,
+            _ => return Err(Error::ProofInvalid),
+        };
 
         // Apply proven state changes.
         if is_player1 {
@@ -540,15 +903,52 @@ impl HeistContract {
         if loot_bits_overlap(public_turn.loot_mask, game.loot_mask) {
             return Err(Error::InvalidTurnData);
         }
+        if game.loot_total_collected + public_turn.loot_delta > game.config.loot_count {
+            return Err(Error::InvalidTurnData);
+        }
         // Accumulate global loot mask and total count.
         game.loot_mask |= public_turn.loot_mask;
         game.loot_total_collected = game
             .loot_total_collected
             .saturating_add(public_turn.loot_delta);
         game.turn_index += 1;
-        game.state_commitment = public_turn.state_commit_after.clone();
+
+        // Re-derive the post-turn state commitment from the updated on-chain values
+        // rather than trusting the client-supplied one, so the state chain cannot be
+        // forked by writing an arbitrary commitment.
+        let expected_state_commit_after = compute_state_commitment(&env, session_id, &game);
+        if public_turn.state_commit_after != expected_state_commit_after {
+            return Err(Error::StateCommitMismatch);
+        }
+        game.state_commitment = expected_state_commit_after;
         game.last_proof_id = proof_id.clone();
         game.last_turn_start_ts = now;
+        // Increment / delay is only credited once the turn has been accepted, so it
+        // can never rescue a clock that already ran out.
+        let credit = clock_credit(&game.config, elapsed);
+        if is_player1 {
+            game.p1_time_remaining = game.p1_time_remaining.saturating_add(credit);
+        } else {
+            game.p2_time_remaining = game.p2_time_remaining.saturating_add(credit);
+        }
+        Self::save_turn_record(
+            &env,
+            session_id,
+            &TurnRecord {
+                turn_index: public_turn.turn_index,
+                player_tag,
+                pos_commit_after: public_turn.pos_commit_after.clone(),
+                score_delta: public_turn.score_delta,
+                loot_mask: public_turn.loot_mask,
+                proof_id: proof_id.clone(),
+                timestamp: now,
+                elapsed,
+            },
+        );
+        // Playing on declines the opponent's draw offer.
+        if game.draw_offered_by.as_ref().is_some_and(|offerer| *offerer != player) {
+            game.draw_offered_by = None;
+        }
 
         // Advance to next active player. If they have already exited,
         // skip them immediately so the backend never needs to call pass_turn().
@@ -570,13 +970,63 @@ impl HeistContract {
         }
 
         Self::save_game(&env, session_id, &game);
-        env.events()
-            .publish((symbol_short!("turn"), session_id), proof_id);
+        TurnAccepted {
+            session_id,
+            turn_index: public_turn.turn_index,
+            player,
+            score_delta: public_turn.score_delta,
+            loot_delta: public_turn.loot_delta,
+            loot_mask: public_turn.loot_mask,
+            exited_flag: public_turn.exited_flag,
+            pos_commit_after: public_turn.pos_commit_after,
+            state_commitment: game.state_commitment,
+            proof_id,
+            p1_time_remaining: game.p1_time_remaining,
+            p2_time_remaining: game.p2_time_remaining,
+            active_player: game.active_player,
+        }
+        .publish(&env);
 
         Self::end_if_finished(env.clone(), session_id)?;
         Ok(())
     }
 
+    /// Moves an in-progress game onto the admin-selected public-input schema.
+    ///
+    /// Games started under an older pi_hash format keep verifying under their
+    /// pinned schema; both players can co-sign this to opt in to the newer one
+    /// (e.g. to have the loot mask bound into each proof) once their clients
+    /// prove with the matching circuit. Schemas only move forward. The game is
+    /// re-pinned to the verifier's current default key along with the schema.
+    pub fn migrate_pi_schema(env: Env, session_id: u32) -> Result<(), Error> {
+        let mut game = Self::require_game(&env, session_id)?;
+        if game.status != GameStatus::Active {
+            return Err(Error::InvalidStatus);
+        }
+        game.player1.require_auth();
+        game.player2.require_auth();
+
+        let target: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::PiSchema)
+            .unwrap_or(PI_SCHEMA_DEFAULT);
+        if target <= game.pi_schema {
+            return Err(Error::UnsupportedPiSchema);
+        }
+
+        game.pi_schema = target;
+        game.vk_id = Self::current_vk_id(&env);
+        Self::save_game(&env, session_id, &game);
+        PiSchemaMigrated {
+            session_id,
+            pi_schema: target,
+            vk_id: game.vk_id,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
     pub fn pass_turn(env: Env, session_id: u32) -> Result<(), Error> {
         let admin: Address = env
             .storage()
@@ -613,6 +1063,12 @@ impl HeistContract {
         game.last_turn_start_ts = env.ledger().timestamp();
 
         Self::save_game(&env, session_id, &game);
+        TurnPassed {
+            session_id,
+            active_player: game.active_player,
+            last_turn_start_ts: game.last_turn_start_ts,
+        }
+        .publish(&env);
         Self::end_if_finished(env.clone(), session_id)?;
         Ok(())
     }
@@ -640,54 +1096,135 @@ impl HeistContract {
         let p1_clock_out = game.p1_time_remaining == 0;
         let p2_clock_out = game.p2_time_remaining == 0;
         let both_exited = game.player1_exited && game.player2_exited;
-        let game_over = both_exited || p1_clock_out || p2_clock_out;
+        let turn_limit_reached =
+            game.config.max_turns > 0 && game.turn_index >= game.config.max_turns;
+        let game_over = both_exited || p1_clock_out || p2_clock_out || turn_limit_reached;
 
         if !game_over {
             return Ok(());
         }
 
-        // Determine winner.
-        let player1_won = if both_exited {
+        // Determine outcome.
+        let outcome = if both_exited {
             // Both exited: higher score wins; tie → earlier exit_turn wins.
-            if game.player1_score != game.player2_score {
-                game.player1_score > game.player2_score
-            } else {
-                // Earlier exit wins (lower turn number; u64::MAX = not exited).
-                game.p1_exit_turn <= game.p2_exit_turn
-            }
+            Self::adjudicate(&game, EndReason::BothExited)
         } else if game.player1_exited && p2_clock_out {
             // Only player1 exited and player2 timed out → player1 wins.
-            true
+            GameOutcome::P1Win(EndReason::Timeout)
         } else if game.player2_exited && p1_clock_out {
             // Only player2 exited and player1 timed out → player2 wins.
-            false
-        } else {
+            GameOutcome::P2Win(EndReason::Timeout)
+        } else if p1_clock_out || p2_clock_out {
             // Neither exited (or only one exited without the other timing out) → score tiebreak.
-            game.player1_score >= game.player2_score
-        };
-
-        let winner = if player1_won {
-            game.player1.clone()
+            if game.player1_score >= game.player2_score {
+                GameOutcome::P1Win(EndReason::Timeout)
+            } else {
+                GameOutcome::P2Win(EndReason::Timeout)
+            }
         } else {
-            game.player2.clone()
+            // Turn limit reached with both clocks still running.
+            Self::adjudicate(&game, EndReason::TurnLimit)
         };
 
-        game.status = GameStatus::Ended;
-        game.winner = Some(winner);
-        Self::save_game(&env, session_id, &game);
-
-        // Notify hub of the result. Non-fatal — the game is already recorded as Ended
-        // on-chain; a hub failure must not prevent the turn transaction from succeeding.
-        if let Some(hub_addr) = env.storage().instance().get::<_, Address>(&DataKey::GameHubAddress) {
-            let hub = GameHubClient::new(&env, &hub_addr);
-            let _ = hub.try_end_game(&session_id, &player1_won);
+        if !both_exited {
+            if p1_clock_out {
+                ClockExpired { session_id, player: game.player1.clone() }.publish(&env);
+            }
+            if p2_clock_out {
+                ClockExpired { session_id, player: game.player2.clone() }.publish(&env);
+            }
         }
 
-        env.events().publish(
-            (symbol_short!("ended"), session_id),
-            (game.player1_score, game.player2_score, player1_won),
-        );
+        Self::settle_game(&env, session_id, &mut game, outcome);
+        Ok(())
+    }
 
+    /// Concedes the game on behalf of `player`; the opponent is recorded as the
+    /// winner. Allowed both before the match begins and during play.
+    pub fn resign(env: Env, session_id: u32, player: Address) -> Result<(), Error> {
+        player.require_auth();
+
+        let mut game = Self::require_game(&env, session_id)?;
+        if game.status == GameStatus::Ended {
+            return Err(Error::GameAlreadyEnded);
+        }
+        let outcome = if player == game.player1 {
+            GameOutcome::P2Win(EndReason::Resignation)
+        } else if player == game.player2 {
+            GameOutcome::P1Win(EndReason::Resignation)
+        } else {
+            return Err(Error::NotPlayer);
+        };
+
+        Self::settle_game(&env, session_id, &mut game, outcome);
+        Ok(())
+    }
+
+    /// Offers the opponent a draw. The offer stands until the opponent accepts
+    /// it or submits a turn of their own.
+    pub fn offer_draw(env: Env, session_id: u32, player: Address) -> Result<(), Error> {
+        player.require_auth();
+
+        let mut game = Self::require_game(&env, session_id)?;
+        if game.status != GameStatus::Active {
+            return Err(Error::InvalidStatus);
+        }
+        if player != game.player1 && player != game.player2 {
+            return Err(Error::NotPlayer);
+        }
+
+        game.draw_offered_by = Some(player.clone());
+        Self::save_game(&env, session_id, &game);
+        DrawOffered { session_id, player }.publish(&env);
+        Ok(())
+    }
+
+    /// Accepts the opponent's pending draw offer, ending the game as a draw.
+    pub fn accept_draw(env: Env, session_id: u32, player: Address) -> Result<(), Error> {
+        player.require_auth();
+
+        let mut game = Self::require_game(&env, session_id)?;
+        if game.status != GameStatus::Active {
+            return Err(Error::InvalidStatus);
+        }
+        if player != game.player1 && player != game.player2 {
+            return Err(Error::NotPlayer);
+        }
+        match &game.draw_offered_by {
+            Some(offerer) if *offerer != player => {}
+            _ => return Err(Error::NoDrawOffer),
+        }
+
+        Self::settle_game(&env, session_id, &mut game, GameOutcome::Draw(EndReason::Agreement));
+        Ok(())
+    }
+
+    /// Ends a game stuck in WaitingReveal once its reveal deadline has passed.
+    ///
+    /// If exactly one player revealed their seed, that player wins. If neither
+    /// did (or both did but begin_match was never co-signed), the game ends in
+    /// a draw. Callable by anyone, like end_if_finished.
+    pub fn claim_reveal_timeout(env: Env, session_id: u32) -> Result<(), Error> {
+        let mut game = Self::require_game(&env, session_id)?;
+        if game.status != GameStatus::WaitingReveal {
+            return Err(Error::InvalidStatus);
+        }
+        if env.ledger().timestamp() < game.reveal_deadline_ts {
+            return Err(Error::RevealWindowOpen);
+        }
+
+        let zero32 = BytesN::from_array(&env, &[0u8; 32]);
+        let p1_revealed = game.p1_seed_reveal != zero32;
+        let p2_revealed = game.p2_seed_reveal != zero32;
+
+        let outcome = if p1_revealed == p2_revealed {
+            GameOutcome::Draw(EndReason::RevealTimeout)
+        } else if p1_revealed {
+            GameOutcome::P1Win(EndReason::RevealTimeout)
+        } else {
+            GameOutcome::P2Win(EndReason::RevealTimeout)
+        };
+        Self::settle_game(&env, session_id, &mut game, outcome);
         Ok(())
     }
 
@@ -698,41 +1235,95 @@ impl HeistContract {
             .get(&DataKey::Admin)
             .expect("admin missing");
         admin.require_auth();
-        let game = Self::require_game(&env, session_id)?;
-        Ok(GameView {
-            player1: game.player1,
-            player2: game.player2,
-            status: game.status,
-            started_at_ts: game.started_at_ts,
-            turn_index: game.turn_index,
-            active_player: game.active_player,
-            player1_score: game.player1_score,
-            player2_score: game.player2_score,
-            loot_total_collected: game.loot_total_collected,
-            loot_mask: game.loot_mask,
-            map_commitment: game.map_commitment,
-            player1_pos_commit: game.player1_pos_commit,
-            player2_pos_commit: game.player2_pos_commit,
-            p1_map_seed_commit: game.p1_map_seed_commit,
-            p2_map_seed_commit: game.p2_map_seed_commit,
-            state_commitment: game.state_commitment,
-            winner: game.winner,
-            last_proof_id: game.last_proof_id,
-            p1_time_remaining: game.p1_time_remaining,
-            p2_time_remaining: game.p2_time_remaining,
-            last_turn_start_ts: game.last_turn_start_ts,
-            player1_exited: game.player1_exited,
-            player2_exited: game.player2_exited,
+        let game = Self::view_game(&env, session_id)?;
+        Ok(Self::to_view(game))
+    }
+
+    /// Same as get_game, without admin auth: everything in GameView is public.
+    pub fn get_game_view(env: Env, session_id: u32) -> Result<GameView, Error> {
+        let game = Self::view_game(&env, session_id)?;
+        Ok(Self::to_view(game))
+    }
+
+    pub fn get_player_view(
+        env: Env,
+        session_id: u32,
+        player: Address,
+    ) -> Result<PlayerView, Error> {
+        let game = Self::view_game(&env, session_id)?;
+        let (player_tag, opponent) = if player == game.player1 {
+            (1, game.player2.clone())
+        } else if player == game.player2 {
+            (2, game.player1.clone())
+        } else {
+            return Err(Error::NotPlayer);
+        };
+
+        // Only the active player's clock is running, and only while Active.
+        let running = game.status == GameStatus::Active;
+        let elapsed = env
+            .ledger()
+            .timestamp()
+            .saturating_sub(game.last_turn_start_ts);
+        let live = |time_remaining: u64, who: &Address| {
+            if running && game.active_player == *who {
+                time_remaining.saturating_sub(elapsed)
+            } else {
+                time_remaining
+            }
+        };
+        let (mine, theirs) = if player_tag == 1 {
+            (game.p1_time_remaining, game.p2_time_remaining)
+        } else {
+            (game.p2_time_remaining, game.p1_time_remaining)
+        };
+
+        Ok(PlayerView {
+            is_my_turn: running && game.active_player == player,
+            my_time_remaining: live(mine, &player),
+            opponent_time_remaining: live(theirs, &opponent),
+            opponent,
+            player_tag,
+            game: Self::to_view(game),
         })
     }
 
+    /// Archived result of a finished game, available long after the game itself
+    /// has expired from temporary storage.
+    pub fn get_match_result(env: Env, session_id: u32) -> Result<MatchResult, Error> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::MatchResult(session_id))
+            .ok_or(Error::GameNotFound)
+    }
+
+    /// Up to `limit` turn records starting at turn `from`, in turn order. Pages
+    /// are capped at MAX_TURNS_PAGE; a short page means the end of the history.
+    pub fn get_turns(env: Env, session_id: u32, from: u32, limit: u32) -> Vec<TurnRecord> {
+        let mut turns = Vec::new(&env);
+        let end = from.saturating_add(limit.min(MAX_TURNS_PAGE));
+        let mut turn_index = from;
+        while turn_index < end {
+            let Some(record) = env
+                .storage()
+                .temporary()
+                .get(&DataKey::TurnRecord(session_id, turn_index))
+            else {
+                break;
+            };
+            turns.push_back(record);
+            turn_index += 1;
+        }
+        turns
+    }
+
     pub fn get_state_commitment(env: Env, session_id: u32) -> Result<BytesN<32>, Error> {
-        let game = Self::require_game(&env, session_id)?;
+        let game = Self::view_game(&env, session_id)?;
         Ok(game.state_commitment.clone())
     }
 
     pub fn get_expected_roll(env: Env, session_id: u32, player: Address) -> Result<u32, Error> {
-        let game = Self::require_game(&env, session_id)?;
+        let game = Self::view_game(&env, session_id)?;
         if game.status != GameStatus::Active {
             return Err(Error::InvalidStatus);
         }
@@ -754,6 +1345,7 @@ impl HeistContract {
             .expect("admin missing");
         admin.require_auth();
         env.storage().instance().set(&DataKey::Admin, &new_admin);
+        AdminChanged { admin: new_admin }.publish(&env);
     }
 
     pub fn set_hub(env: Env, new_hub: Address) {
@@ -766,6 +1358,7 @@ impl HeistContract {
         env.storage()
             .instance()
             .set(&DataKey::GameHubAddress, &new_hub);
+        HubChanged { hub: new_hub }.publish(&env);
     }
 
     pub fn set_verifier(env: Env, new_verifier: Address) {
@@ -778,6 +1371,59 @@ impl HeistContract {
         env.storage()
             .instance()
             .set(&DataKey::VerifierAddress, &new_verifier);
+        VerifierChanged {
+            verifier: new_verifier,
+        }
+        .publish(&env);
+    }
+
+    /// Selects the turn public-input schema pinned by games that begin from now on.
+    /// Games already in progress keep the schema they started with.
+    pub fn set_pi_schema(env: Env, pi_schema: u32) -> Result<(), Error> {
+        let admin: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .expect("admin missing");
+        admin.require_auth();
+        if !pi_schema_supported(pi_schema) {
+            return Err(Error::UnsupportedPiSchema);
+        }
+        env.storage().instance().set(&DataKey::PiSchema, &pi_schema);
+        PiSchemaSet { pi_schema }.publish(&env);
+        Ok(())
+    }
+
+    /// Enables escrow mode with `token` for games started from now on, or disables
+    /// it with None. Games already started keep the token they were created with.
+    pub fn set_stake_token(env: Env, token: Option<Address>) {
+        let admin: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .expect("admin missing");
+        admin.require_auth();
+        match &token {
+            Some(token) => env.storage().instance().set(&DataKey::StakeToken, token),
+            None => env.storage().instance().remove(&DataKey::StakeToken),
+        }
+        StakeTokenSet { token }.publish(&env);
+    }
+
+    /// Sets the fee (in basis points of the pot) paid to the admin on escrow payouts.
+    pub fn set_fee_bps(env: Env, fee_bps: u32) -> Result<(), Error> {
+        let admin: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .expect("admin missing");
+        admin.require_auth();
+        if fee_bps > MAX_FEE_BPS {
+            return Err(Error::InvalidFee);
+        }
+        env.storage().instance().set(&DataKey::FeeBps, &fee_bps);
+        FeeBpsSet { fee_bps }.publish(&env);
+        Ok(())
     }
 
     pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) {
@@ -787,19 +1433,361 @@ impl HeistContract {
             .get(&DataKey::Admin)
             .expect("admin missing");
         admin.require_auth();
-        env.deployer().update_current_contract_wasm(new_wasm_hash);
+        env.deployer()
+            .update_current_contract_wasm(new_wasm_hash.clone());
+        Upgraded { new_wasm_hash }.publish(&env);
+    }
+
+    /// Escrows stakes, notifies the hub and stores a new game in WaitingReveal.
+    /// Callers have already checked the session id and collected both players'
+    /// auth.
+    fn open_game(
+        env: &Env,
+        session_id: u32,
+        player1: Address,
+        player2: Address,
+        player1_points: i128,
+        player2_points: i128,
+        p1_seed_commit: BytesN<32>,
+        p2_seed_commit: BytesN<32>,
+        p1_map_seed_commit: BytesN<32>,
+        p2_map_seed_commit: BytesN<32>,
+        config: GameConfig,
+    ) -> Result<(), Error> {
+        if !game_config_valid(&config) {
+            return Err(Error::InvalidConfig);
+        }
+
+        // In escrow mode both stakes are pulled into the contract up front, so the
+        // payout never depends on the hub.
+        let stake_token: Option<Address> = env.storage().instance().get(&DataKey::StakeToken);
+        if let Some(token_addr) = &stake_token {
+            // The payout takes fee_bps of the pot, so pot · MAX_FEE_BPS must fit.
+            let pot_fits = player1_points
+                .checked_add(player2_points)
+                .and_then(|pot| pot.checked_mul(MAX_FEE_BPS as i128))
+                .is_some();
+            if player1_points < 0 || player2_points < 0 || !pot_fits {
+                return Err(Error::InvalidStake);
+            }
+            let token = token::Client::new(env, token_addr);
+            let contract = env.current_contract_address();
+            if player1_points > 0 {
+                token.transfer(&player1, &contract, &player1_points);
+            }
+            if player2_points > 0 {
+                token.transfer(&player2, &contract, &player2_points);
+            }
+        }
+        let fee_bps: u32 = env.storage().instance().get(&DataKey::FeeBps).unwrap_or(0);
+
+        // Notify hub of the new session. Non-fatal — game proceeds even if hub is
+        // unavailable (e.g. during isolated testnet deployments).
+        if let Some(hub_addr) = env.storage().instance().get::<_, Address>(&DataKey::GameHubAddress) {
+            let hub = GameHubClient::new(env, &hub_addr);
+            let _ = hub.try_start_game(
+                &env.current_contract_address(),
+                &session_id,
+                &player1,
+                &player2,
+                &player1_points,
+                &player2_points,
+            );
+        }
+
+        let zero32 = BytesN::from_array(env, &[0u8; 32]);
+        let game = Game {
+            player1: player1.clone(),
+            player2: player2.clone(),
+            player1_points,
+            player2_points,
+            status: GameStatus::WaitingReveal,
+            p1_seed_commit,
+            p2_seed_commit,
+            p1_seed_reveal: zero32.clone(),
+            p2_seed_reveal: zero32.clone(),
+            session_seed: zero32.clone(),
+            p1_map_seed_commit,
+            p2_map_seed_commit,
+            map_commitment: zero32.clone(),
+            player1_pos_commit: zero32.clone(),
+            player2_pos_commit: zero32.clone(),
+            player1_score: 0,
+            player2_score: 0,
+            loot_total_collected: 0,
+            loot_mask: 0i128,
+            state_commitment: zero32.clone(),
+            started_at_ts: 0,
+            turn_index: 0,
+            active_player: player1.clone(),
+            winner: None,
+            last_proof_id: zero32,
+            p1_time_remaining: config.time_per_player,
+            p2_time_remaining: config.time_per_player,
+            last_turn_start_ts: 0,
+            player1_exited: false,
+            player2_exited: false,
+            p1_exit_turn: u64::MAX,
+            p2_exit_turn: u64::MAX,
+            pi_schema: 0,
+            vk_id: None,
+            reveal_deadline_ts: env.ledger().timestamp() + REVEAL_TIMEOUT_SECONDS,
+            stake_token,
+            fee_bps,
+            draw_offered_by: None,
+            outcome: GameOutcome::Pending,
+            config,
+        };
+
+        Self::save_game(env, session_id, &game);
+        GameCreated {
+            session_id,
+            player1: game.player1,
+            player2: game.player2,
+            player1_points,
+            player2_points,
+            config: game.config,
+            reveal_deadline_ts: game.reveal_deadline_ts,
+        }
+        .publish(env);
+        Ok(())
+    }
+
+    /// A session id is taken while its game is live or once a result has been
+    /// archived under it.
+    fn session_id_taken(env: &Env, session_id: u32) -> bool {
+        Self::load_game(env, session_id).is_some()
+            || env
+                .storage()
+                .persistent()
+                .has(&DataKey::MatchResult(session_id))
+    }
+
+    /// The verifier's default key id; None if it has none (or predates the
+    /// VK registry).
+    fn current_vk_id(env: &Env) -> Option<BytesN<32>> {
+        let verifier_addr: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::VerifierAddress)
+            .expect("verifier missing");
+        match ZkVerifierClient::new(env, &verifier_addr).try_get_vk_hash() {
+            Ok(Ok(vk_id)) => vk_id,
+            _ => None,
+        }
+    }
+
+    /// Higher score wins; on equal scores the earlier exit wins (a player who
+    /// exited beats one who did not). Fully level games are drawn.
+    fn adjudicate(game: &Game, reason: EndReason) -> GameOutcome {
+        if game.player1_score != game.player2_score {
+            if game.player1_score > game.player2_score {
+                GameOutcome::P1Win(reason)
+            } else {
+                GameOutcome::P2Win(reason)
+            }
+        } else if game.p1_exit_turn != game.p2_exit_turn {
+            // Lower turn number exits first; u64::MAX = not exited.
+            if game.p1_exit_turn < game.p2_exit_turn {
+                GameOutcome::P1Win(reason)
+            } else {
+                GameOutcome::P2Win(reason)
+            }
+        } else {
+            GameOutcome::Draw(reason)
+        }
+    }
+
+    /// Records the outcome, settles stakes, notifies the hub and emits
+    /// `GameEnded`.
+    fn settle_game(env: &Env, session_id: u32, game: &mut Game, outcome: GameOutcome) {
+        let winner = match outcome {
+            GameOutcome::P1Win(_) => Some(game.player1.clone()),
+            GameOutcome::P2Win(_) => Some(game.player2.clone()),
+            GameOutcome::Draw(_) | GameOutcome::Pending => None,
+        };
+
+        game.status = GameStatus::Ended;
+        game.winner = winner.clone();
+        game.draw_offered_by = None;
+        game.outcome = outcome.clone();
+        Self::save_game(env, session_id, game);
+        Self::archive_result(env, session_id, game);
+        match &winner {
+            Some(winner) => Self::pay_out_stakes(env, game, winner),
+            None => Self::refund_stakes(env, game),
+        }
+
+        // Notify hub of the result. Non-fatal — the game is already recorded as Ended
+        // on-chain; a hub failure must not prevent the turn transaction from succeeding.
+        // The hub only records a winner, so a draw, like a failed call, is published as
+        // ResultNotReported for whoever keeps the hub in sync.
+        if let Some(hub_addr) = env.storage().instance().get::<_, Address>(&DataKey::GameHubAddress) {
+            let hub = GameHubClient::new(env, &hub_addr);
+            let reported = match &winner {
+                Some(winner) => hub.try_end_game(&session_id, &(*winner == game.player1)).is_ok(),
+                None => false,
+            };
+            if !reported {
+                ResultNotReported {
+                    session_id,
+                    outcome: outcome.clone(),
+                }
+                .publish(env);
+            }
+        }
+
+        GameEnded {
+            session_id,
+            player1_score: game.player1_score,
+            player2_score: game.player2_score,
+            winner,
+            outcome,
+        }
+        .publish(env);
+    }
+
+    /// Sends the escrowed pot to `winner`, minus the admin fee pinned on the game.
+    fn pay_out_stakes(env: &Env, game: &Game, winner: &Address) {
+        let Some(token_addr) = &game.stake_token else {
+            return;
+        };
+        let token = token::Client::new(env, token_addr);
+        let contract = env.current_contract_address();
+        // open_game only escrows stakes for which neither step can overflow.
+        let pot = game
+            .player1_points
+            .checked_add(game.player2_points)
+            .expect("stake pot overflow");
+        let fee = pot
+            .checked_mul(game.fee_bps as i128)
+            .expect("stake fee overflow")
+            / MAX_FEE_BPS as i128;
+        if fee > 0 {
+            let admin: Address = env
+                .storage()
+                .instance()
+                .get(&DataKey::Admin)
+                .expect("admin missing");
+            token.transfer(&contract, &admin, &fee);
+        }
+        if pot - fee > 0 {
+            token.transfer(&contract, winner, &(pot - fee));
+        }
+    }
+
+    /// Returns each player's escrowed stake (drawn games, no fee).
+    fn refund_stakes(env: &Env, game: &Game) {
+        let Some(token_addr) = &game.stake_token else {
+            return;
+        };
+        let token = token::Client::new(env, token_addr);
+        let contract = env.current_contract_address();
+        if game.player1_points > 0 {
+            token.transfer(&contract, &game.player1, &game.player1_points);
+        }
+        if game.player2_points > 0 {
+            token.transfer(&contract, &game.player2, &game.player2_points);
+        }
+    }
+
+    fn archive_result(env: &Env, session_id: u32, game: &Game) {
+        let key = DataKey::MatchResult(session_id);
+        let result = MatchResult {
+            player1: game.player1.clone(),
+            player2: game.player2.clone(),
+            player1_score: game.player1_score,
+            player2_score: game.player2_score,
+            winner: game.winner.clone(),
+            outcome: game.outcome.clone(),
+            turn_count: game.turn_index,
+            final_state_commitment: game.state_commitment.clone(),
+            last_proof_id: game.last_proof_id.clone(),
+            ended_at_ts: env.ledger().timestamp(),
+        };
+        env.storage().persistent().set(&key, &result);
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, MATCH_RESULT_TTL_LEDGERS, MATCH_RESULT_TTL_LEDGERS);
+    }
+
+    fn to_view(game: Game) -> GameView {
+        GameView {
+            player1: game.player1,
+            player2: game.player2,
+            status: game.status,
+            started_at_ts: game.started_at_ts,
+            turn_index: game.turn_index,
+            active_player: game.active_player,
+            player1_score: game.player1_score,
+            player2_score: game.player2_score,
+            loot_total_collected: game.loot_total_collected,
+            loot_mask: game.loot_mask,
+            map_commitment: game.map_commitment,
+            player1_pos_commit: game.player1_pos_commit,
+            player2_pos_commit: game.player2_pos_commit,
+            p1_map_seed_commit: game.p1_map_seed_commit,
+            p2_map_seed_commit: game.p2_map_seed_commit,
+            state_commitment: game.state_commitment,
+            winner: game.winner,
+            last_proof_id: game.last_proof_id,
+            p1_time_remaining: game.p1_time_remaining,
+            p2_time_remaining: game.p2_time_remaining,
+            last_turn_start_ts: game.last_turn_start_ts,
+            player1_exited: game.player1_exited,
+            player2_exited: game.player2_exited,
+            pi_schema: game.pi_schema,
+            vk_id: game.vk_id.clone(),
+            reveal_deadline_ts: game.reveal_deadline_ts,
+            stake_token: game.stake_token,
+            draw_offered_by: game.draw_offered_by,
+            outcome: game.outcome,
+            config: game.config,
+        }
+    }
+
+    /// Turn records share the game's temporary-storage lifetime.
+    fn save_turn_record(env: &Env, session_id: u32, record: &TurnRecord) {
+        let key = DataKey::TurnRecord(session_id, record.turn_index);
+        env.storage().temporary().set(&key, record);
+        env.storage()
+            .temporary()
+            .extend_ttl(&key, GAME_TTL_LEDGERS, GAME_TTL_LEDGERS);
     }
 
     fn load_game(env: &Env, session_id: u32) -> Option<Game> {
-        env.storage().temporary().get(&DataKey::Game(session_id))
+        if let Some(game) = env.storage().temporary().get(&DataKey::GameV2(session_id)) {
+            return Some(game);
+        }
+        // A game started before the upgrade: move it over to the current layout.
+        let legacy_key = DataKey::Game(session_id);
+        let legacy: LegacyGame = env.storage().temporary().get(&legacy_key)?;
+        let game = Game::from_legacy(env, legacy);
+        Self::save_game(env, session_id, &game);
+        env.storage().temporary().remove(&legacy_key);
+        Some(game)
     }
 
     fn require_game(env: &Env, session_id: u32) -> Result<Game, Error> {
         Self::load_game(env, session_id).ok_or(Error::GameNotFound)
     }
 
+    /// Like require_game, but a game from before the upgrade is only converted
+    /// in memory: read-only calls leave storage as they found it.
+    fn view_game(env: &Env, session_id: u32) -> Result<Game, Error> {
+        if let Some(game) = env.storage().temporary().get(&DataKey::GameV2(session_id)) {
+            return Ok(game);
+        }
+        let legacy: LegacyGame = env
+            .storage()
+            .temporary()
+            .get(&DataKey::Game(session_id))
+            .ok_or(Error::GameNotFound)?;
+        Ok(Game::from_legacy(env, legacy))
+    }
+
     fn save_game(env: &Env, session_id: u32, game: &Game) {
-        let key = DataKey::Game(session_id);
+        let key = DataKey::GameV2(session_id);
         env.storage().temporary().set(&key, game);
         env.storage()
             .temporary()
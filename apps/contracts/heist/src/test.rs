@@ -5,9 +5,18 @@ use soroban_sdk::{
 };
 
 use crate::{
-    engine::{commit_hash, compute_turn_pi_hash, derive_session_seed, roll_value},
-    GameStatus, HeistContract, HeistContractClient, TurnZkPublic,
+    engine::{
+        commit_hash, compute_state_commitment, compute_turn_pi_hash, compute_turn_pi_hash_v2,
+        derive_session_seed, roll_value, PI_SCHEMA_DEFAULT, PI_SCHEMA_V1,
+        PI_SCHEMA_V2, PI_SCHEMA_V6, BOARD_SIDE, LOOT_COUNT, PLAYER_TIME_SECONDS,
+        REVEAL_TIMEOUT_SECONDS,
+    },
+    events::{GameEnded, ResultNotReported, TurnAccepted},
+    ClockMode, DataKey, EndReason, Error, Game, GameConfig, GameOutcome, GameStatus, HeistContract,
+    HeistContractClient, LegacyGame, TurnZkPublic,
 };
+#[cfg(feature = "multi-input-schema")]
+use crate::engine::compute_turn_public_inputs_v6;
 
 #[contract]
 pub struct MockHubContract;
@@ -56,19 +65,92 @@ impl MockHubContract {
     }
 }
 
+/// Mock hub whose `end_game` always fails.
+#[contract]
+pub struct MockFailingHubContract;
+
+#[contractimpl]
+impl MockFailingHubContract {
+    pub fn end_game(_env: Env, _session_id: u32, _player1_won: bool) {
+        panic!("hub unavailable");
+    }
+}
+
+/// Whether the last call published `ResultNotReported` for `session_id`.
+fn result_not_reported(env: &Env, heist_id: &Address, session_id: u32, outcome: GameOutcome) -> bool {
+    use soroban_sdk::{testutils::Events, Event};
+
+    let event = ResultNotReported { session_id, outcome }.to_xdr(env, heist_id);
+    env.events().all().filter_by_contract(heist_id).events().contains(&event)
+}
+
+#[contracttype]
+#[derive(Clone)]
+enum VerifierDataKey {
+    DefaultVk,
+    LastVk,
+}
+
 /// Mock verifier: matches the actual ZkVerifier trait (no public_inputs_hash param).
-/// Returns keccak256(proof_blob) as the proof_id.
+/// Returns keccak256(proof_blob) as the proof_id and records the key each proof
+/// was checked against (the zero hash for the default key).
 #[contract]
 pub struct MockVerifierContract;
 
 #[contractimpl]
 impl MockVerifierContract {
-    pub fn verify_proof_with_stored_vk(
+    pub fn consume_proof(
         env: Env,
+        consumer: Address,
+        vk_id: Option<BytesN<32>>,
         proof_blob: Bytes,
     ) -> BytesN<32> {
+        consumer.require_auth();
+        let vk_id = vk_id.unwrap_or_else(|| BytesN::from_array(&env, &[0u8; 32]));
+        env.storage().instance().set(&VerifierDataKey::LastVk, &vk_id);
         env.crypto().keccak256(&proof_blob).into()
     }
+
+    pub fn get_vk_hash(env: Env) -> Option<BytesN<32>> {
+        env.storage().instance().get(&VerifierDataKey::DefaultVk)
+    }
+
+    pub fn set_default_vk(env: Env, vk_id: BytesN<32>) {
+        env.storage().instance().set(&VerifierDataKey::DefaultVk, &vk_id);
+    }
+
+    pub fn last_vk(env: Env) -> Option<BytesN<32>> {
+        env.storage().instance().get(&VerifierDataKey::LastVk)
+    }
+}
+
+/// Mock verifier that fails every proof with zk-verifier's `PairingFailed` code.
+#[contract]
+pub struct MockRejectingVerifierContract;
+
+#[contractimpl]
+impl MockRejectingVerifierContract {
+    pub fn consume_proof(
+        _env: Env,
+        _consumer: Address,
+        _vk_id: Option<BytesN<32>>,
+        _proof_blob: Bytes,
+    ) -> Result<BytesN<32>, soroban_sdk::Error> {
+        Err(soroban_sdk::Error::from_contract_error(6))
+    }
+}
+
+/// Rules used by every test game unless a test overrides them.
+fn default_config() -> GameConfig {
+    GameConfig {
+        time_per_player: PLAYER_TIME_SECONDS,
+        clock_mode: ClockMode::Fischer,
+        increment_seconds: 0,
+        max_turns: 0,
+        loot_count: LOOT_COUNT,
+        board_width: BOARD_SIDE,
+        board_height: BOARD_SIDE,
+    }
 }
 
 fn make_commit(env: &Env, seed: &BytesN<32>) -> BytesN<32> {
@@ -90,7 +172,7 @@ fn make_test_proof_blob(env: &Env, pi_hash: &BytesN<32>) -> Bytes {
         blob.push_back(pi_hash.get(k).unwrap_or(0));
         k += 1;
     }
-    // Dummy proof bytes: need total >= 292 (4 + 32 + padding).
+    // Dummy proof bytes: total exactly 292 (4 + 32 + 256).
     let mut p = 0u32;
     while p < 256 {
         blob.push_back(0xAB);
@@ -99,8 +181,51 @@ fn make_test_proof_blob(env: &Env, pi_hash: &BytesN<32>) -> Bytes {
     blob
 }
 
+/// Proof blob stub carrying several public inputs, laid out as
+/// [n_pub][n_pub × 32 inputs][256 dummy proof bytes].
+#[cfg(feature = "multi-input-schema")]
+fn make_test_proof_blob_multi(env: &Env, inputs: &soroban_sdk::Vec<BytesN<32>>) -> Bytes {
+    let mut blob = Bytes::new(env);
+    blob.extend_from_array(&inputs.len().to_be_bytes());
+    for input in inputs.iter() {
+        blob.append(&input.into());
+    }
+    blob.extend_from_array(&[0xABu8; 256]);
+    blob
+}
+
+/// The game as the contract holds it, including fields GameView leaves out.
+fn stored_game(env: &Env, heist_id: &Address, session_id: u32) -> Game {
+    env.as_contract(heist_id, || HeistContract::view_game(env, session_id).unwrap())
+}
+
+/// Expected pi_hash for `public_turn` under schema v2 (pinned by the setup
+/// helpers).
+fn turn_pi_hash(env: &Env, heist_id: &Address, public_turn: &TurnZkPublic, player_tag: u32) -> BytesN<32> {
+    env.as_contract(heist_id, || {
+        compute_turn_pi_hash_v2(env, public_turn, player_tag)
+    })
+}
+
 fn setup_active_game(
     session_id: u32,
+) -> (
+    Env,
+    Address,
+    Address,
+    Address,
+    MockHubContractClient<'static>,
+    BytesN<32>,
+    BytesN<32>,
+    BytesN<32>,
+    BytesN<32>,
+) {
+    setup_active_game_with_config(session_id, default_config())
+}
+
+fn setup_active_game_with_config(
+    session_id: u32,
+    config: GameConfig,
 ) -> (
     Env,
     Address, // player1
@@ -128,6 +253,8 @@ fn setup_active_game(
     );
 
     let heist = HeistContractClient::new(&env, &heist_id);
+    // Turn helpers below prove the loot-mask-bound schema, not the v1 default.
+    heist.set_pi_schema(&PI_SCHEMA_V2);
 
     let s1 = BytesN::from_array(&env, &[1u8; 32]);
     let s2 = BytesN::from_array(&env, &[2u8; 32]);
@@ -140,13 +267,13 @@ fn setup_active_game(
     let mc1 = make_commit(&env, &ms1);
     let mc2 = make_commit(&env, &ms2);
 
-    heist.start_game(&session_id, &player1, &player2, &50, &50, &c1, &c2, &mc1, &mc2);
+    heist.start_game(&session_id, &player1, &player2, &50, &50, &c1, &c2, &mc1, &mc2, &config);
     heist.reveal_seed(&session_id, &player1, &s1);
     heist.reveal_seed(&session_id, &player2, &s2);
 
     let map_commitment = BytesN::from_array(&env, &[0x11u8; 32]);
     let p1_pos_commit = BytesN::from_array(&env, &[0x22u8; 32]);
-    let p2_pos_commit = BytesN::from_array(&env, &[0x33u8; 32]);
+    let p2_pos_commit = BytesN::from_array(&env, &[0x23u8; 32]);
 
     heist.begin_match(&session_id, &map_commitment, &p1_pos_commit, &p2_pos_commit);
 
@@ -183,7 +310,7 @@ fn start_reveal_begin_flow() {
     let mc1 = make_commit(&env, &BytesN::from_array(&env, &[0xAAu8; 32]));
     let mc2 = make_commit(&env, &BytesN::from_array(&env, &[0xBBu8; 32]));
 
-    heist.start_game(&session_id, &player1, &player2, &50, &50, &c1, &c2, &mc1, &mc2);
+    heist.start_game(&session_id, &player1, &player2, &50, &50, &c1, &c2, &mc1, &mc2, &default_config());
     heist.reveal_seed(&session_id, &player1, &s1);
     heist.reveal_seed(&session_id, &player2, &s2);
 
@@ -229,7 +356,7 @@ fn tie_break_player1_on_timeout() {
     let mc1 = make_commit(&env, &BytesN::from_array(&env, &[0xCCu8; 32]));
     let mc2 = make_commit(&env, &BytesN::from_array(&env, &[0xDDu8; 32]));
 
-    heist.start_game(&session_id, &player1, &player2, &50, &50, &c1, &c2, &mc1, &mc2);
+    heist.start_game(&session_id, &player1, &player2, &50, &50, &c1, &c2, &mc1, &mc2, &default_config());
     heist.reveal_seed(&session_id, &player1, &s1);
     heist.reveal_seed(&session_id, &player2, &s2);
 
@@ -277,7 +404,7 @@ fn expected_roll_matches_engine_formula() {
     let mc1 = make_commit(&env, &BytesN::from_array(&env, &[0xEEu8; 32]));
     let mc2 = make_commit(&env, &BytesN::from_array(&env, &[0xFFu8; 32]));
 
-    heist.start_game(&session_id, &player1, &player2, &10, &10, &c1, &c2, &mc1, &mc2);
+    heist.start_game(&session_id, &player1, &player2, &10, &10, &c1, &c2, &mc1, &mc2, &default_config());
     heist.reveal_seed(&session_id, &player1, &s1);
     heist.reveal_seed(&session_id, &player2, &s2);
     let map_commitment = BytesN::from_array(&env, &[0x77u8; 32]);
@@ -311,6 +438,7 @@ fn submit_turn_updates_state() {
         (admin.clone(), hub_id.clone(), verifier_id.clone()),
     );
     let heist = HeistContractClient::new(&env, &heist_id);
+    heist.set_pi_schema(&PI_SCHEMA_V2);
 
     let session_id = 103u32;
     let s1 = BytesN::from_array(&env, &[7u8; 32]);
@@ -320,7 +448,7 @@ fn submit_turn_updates_state() {
     let mc1 = make_commit(&env, &BytesN::from_array(&env, &[0x11u8; 32]));
     let mc2 = make_commit(&env, &BytesN::from_array(&env, &[0x22u8; 32]));
 
-    heist.start_game(&session_id, &player1, &player2, &10, &10, &c1, &c2, &mc1, &mc2);
+    heist.start_game(&session_id, &player1, &player2, &10, &10, &c1, &c2, &mc1, &mc2, &default_config());
     heist.reveal_seed(&session_id, &player1, &s1);
     heist.reveal_seed(&session_id, &player2, &s2);
 
@@ -333,7 +461,13 @@ fn submit_turn_updates_state() {
     // Get the initial state commitment
     let state_commit_before = heist.get_state_commitment(&session_id);
     let new_pos_commit = BytesN::from_array(&env, &{let mut a=[0u8;32]; a[31]=0x03; a});
-    let state_commit_after = BytesN::from_array(&env, &[0xEEu8; 32]);
+    let mut next = stored_game(&env, &heist_id, session_id);
+    next.turn_index = 1;
+    next.player1_score = 1;
+    next.player1_pos_commit = new_pos_commit.clone();
+    let state_commit_after = env.as_contract(&heist_id, || {
+        compute_state_commitment(&env, session_id, &next)
+    });
 
     // Build the public turn data
     let public_turn = TurnZkPublic {
@@ -352,20 +486,7 @@ fn submit_turn_updates_state() {
     };
 
     // Compute the pi_hash directly using the engine function (player1 = tag 1)
-    let pi_hash = env.as_contract(&heist_id, || {
-        compute_turn_pi_hash(
-            &env,
-            session_id,
-            public_turn.turn_index,
-            1u32,
-            &public_turn.pos_commit_before,
-            &public_turn.pos_commit_after,
-            public_turn.score_delta,
-            public_turn.loot_delta,
-            public_turn.no_path_flag,
-            public_turn.exited_flag,
-        )
-    });
+    let pi_hash = turn_pi_hash(&env, &heist_id, &public_turn, 1);
     let proof_blob = make_test_proof_blob(&env, &pi_hash);
 
     heist.submit_turn(&session_id, &player1, &proof_blob, &public_turn);
@@ -378,3 +499,1282 @@ fn submit_turn_updates_state() {
     assert_eq!(g.state_commitment, state_commit_after);
     assert_eq!(g.active_player, player2);
 }
+
+#[test]
+fn submit_turn_rejects_blobs_that_are_not_exactly_one_proof_long() {
+    let session_id = 140u32;
+    let (env, player1, _player2, heist_id, _hub, _session_seed, _map_commitment, _p1_pos, _p2_pos) =
+        setup_active_game(session_id);
+    let heist = HeistContractClient::new(&env, &heist_id);
+
+    let turn = first_turn(&env, &heist_id, session_id, &player1, 0, 0, 0);
+    let blob = make_test_proof_blob(&env, &turn_pi_hash(&env, &heist_id, &turn, 1));
+    let mut padded = blob.clone();
+    padded.push_back(0);
+    for wrong_length in [blob.slice(0..blob.len() - 1), padded] {
+        assert_eq!(
+            heist.try_submit_turn(&session_id, &player1, &wrong_length, &turn),
+            Err(Ok(Error::ProofRequired))
+        );
+    }
+    heist.submit_turn(&session_id, &player1, &blob, &turn);
+    assert_eq!(heist.get_game(&session_id).turn_index, 1);
+}
+
+#[test]
+fn submit_turn_rejects_forged_state_commit_after() {
+    let session_id = 104u32;
+    let (env, player1, _player2, heist_id, _hub, _session_seed, _map_commitment, p1_pos_commit, _p2_pos_commit) =
+        setup_active_game(session_id);
+    let heist = HeistContractClient::new(&env, &heist_id);
+
+    let state_commit_before = heist.get_state_commitment(&session_id);
+    let new_pos_commit = BytesN::from_array(&env, &{let mut a=[0u8;32]; a[31]=0x03; a});
+
+    // Claims a post-turn commitment that does not follow from the updated state.
+    let public_turn = TurnZkPublic {
+        session_id,
+        turn_index: 0,
+        player: player1.clone(),
+        score_delta: 0,
+        loot_delta: 0,
+        loot_mask: 0i128,
+        pos_commit_before: p1_pos_commit.clone(),
+        pos_commit_after: new_pos_commit.clone(),
+        state_commit_before: state_commit_before.clone(),
+        state_commit_after: BytesN::from_array(&env, &[0xEEu8; 32]),
+        no_path_flag: false,
+        exited_flag: false,
+    };
+
+    let pi_hash = turn_pi_hash(&env, &heist_id, &public_turn, 1);
+    let proof_blob = make_test_proof_blob(&env, &pi_hash);
+
+    let res = heist.try_submit_turn(&session_id, &player1, &proof_blob, &public_turn);
+    assert_eq!(res, Err(Ok(Error::StateCommitMismatch)));
+
+    let g = heist.get_game(&session_id);
+    assert_eq!(g.turn_index, 0);
+    assert_eq!(g.state_commitment, state_commit_before);
+}
+
+#[test]
+fn submit_turn_rejects_loot_mask_not_bound_by_proof() {
+    let session_id = 105u32;
+    let (env, player1, _player2, heist_id, _hub, _session_seed, _map_commitment, p1_pos_commit, _p2_pos_commit) =
+        setup_active_game(session_id);
+    let heist = HeistContractClient::new(&env, &heist_id);
+
+    let state_commit_before = heist.get_state_commitment(&session_id);
+    let new_pos_commit = BytesN::from_array(&env, &{let mut a=[0u8;32]; a[31]=0x03; a});
+    let mut next = stored_game(&env, &heist_id, session_id);
+    next.turn_index = 1;
+    next.player1_score = 1;
+    next.player1_pos_commit = new_pos_commit.clone();
+    let state_commit_after = env.as_contract(&heist_id, || {
+        compute_state_commitment(&env, session_id, &next)
+    });
+
+    // The proof was generated for cell 0; the submitted turn claims cell 5 instead.
+    let mut public_turn = TurnZkPublic {
+        session_id,
+        turn_index: 0,
+        player: player1.clone(),
+        score_delta: 1,
+        loot_delta: 1,
+        loot_mask: 1i128,
+        pos_commit_before: p1_pos_commit.clone(),
+        pos_commit_after: new_pos_commit.clone(),
+        state_commit_before: state_commit_before.clone(),
+        state_commit_after,
+        no_path_flag: false,
+        exited_flag: false,
+    };
+    let pi_hash = turn_pi_hash(&env, &heist_id, &public_turn, 1);
+    let proof_blob = make_test_proof_blob(&env, &pi_hash);
+    public_turn.loot_mask = 1i128 << 5;
+
+    let res = heist.try_submit_turn(&session_id, &player1, &proof_blob, &public_turn);
+    assert_eq!(res, Err(Ok(Error::InvalidPublicInput)));
+    assert_eq!(heist.get_game(&session_id).loot_mask, 0);
+}
+
+/// Player1's first turn on a game from `setup_active_game`, moving to pos commit 0x..03.
+fn first_turn(
+    env: &Env,
+    heist_id: &Address,
+    session_id: u32,
+    player1: &Address,
+    score_delta: i128,
+    loot_delta: u32,
+    loot_mask: i128,
+) -> TurnZkPublic {
+    let new_pos_commit = BytesN::from_array(env, &{let mut a=[0u8;32]; a[31]=0x03; a});
+    let mut next = stored_game(env, heist_id, session_id);
+    let pos_commit_before = next.player1_pos_commit.clone();
+    let state_commit_before = next.state_commitment.clone();
+    next.turn_index += 1;
+    next.player1_score += score_delta;
+    next.player1_pos_commit = new_pos_commit.clone();
+    let state_commit_after = env.as_contract(heist_id, || {
+        compute_state_commitment(env, session_id, &next)
+    });
+    TurnZkPublic {
+        session_id,
+        turn_index: 0,
+        player: player1.clone(),
+        score_delta,
+        loot_delta,
+        loot_mask,
+        pos_commit_before,
+        pos_commit_after: new_pos_commit,
+        state_commit_before,
+        state_commit_after,
+        no_path_flag: false,
+        exited_flag: false,
+    }
+}
+
+/// Player2's turn 1 (after `first_turn`) in which they stay put and collect nothing.
+fn player2_idle_turn(
+    env: &Env,
+    heist_id: &Address,
+    session_id: u32,
+    player2: &Address,
+) -> TurnZkPublic {
+    let mut next = stored_game(env, heist_id, session_id);
+    let state_commit_before = next.state_commitment.clone();
+    next.turn_index += 1;
+    let state_commit_after = env.as_contract(heist_id, || {
+        compute_state_commitment(env, session_id, &next)
+    });
+    TurnZkPublic {
+        session_id,
+        turn_index: 1,
+        player: player2.clone(),
+        score_delta: 0,
+        loot_delta: 0,
+        loot_mask: 0,
+        pos_commit_before: next.player2_pos_commit.clone(),
+        pos_commit_after: next.player2_pos_commit,
+        state_commit_before,
+        state_commit_after,
+        no_path_flag: true,
+        exited_flag: false,
+    }
+}
+
+#[test]
+fn submit_turn_accepts_scores_within_the_hazard_penalty_range() {
+    let session_id = 106u32;
+    let (env, player1, _player2, heist_id, _hub, _session_seed, _map_commitment, _p1_pos_commit, _p2_pos_commit) =
+        setup_active_game(session_id);
+    let heist = HeistContractClient::new(&env, &heist_id);
+
+    // Two loot cells, at worst 3 cameras and 2 lasers: 2 - 3 - 4 = -5.
+    let below_worst_case = first_turn(&env, &heist_id, session_id, &player1, -6, 2, 0b11);
+    let proof_blob = make_test_proof_blob(&env, &turn_pi_hash(&env, &heist_id, &below_worst_case, 1));
+    let res = heist.try_submit_turn(&session_id, &player1, &proof_blob, &below_worst_case);
+    assert_eq!(res, Err(Ok(Error::InvalidScoreDelta)));
+
+    // One camera and one laser: 2 - 1 - 2 = -1.
+    let public_turn = first_turn(&env, &heist_id, session_id, &player1, -1, 2, 0b11);
+    let proof_blob = make_test_proof_blob(&env, &turn_pi_hash(&env, &heist_id, &public_turn, 1));
+    heist.submit_turn(&session_id, &player1, &proof_blob, &public_turn);
+
+    let g = heist.get_game(&session_id);
+    assert_eq!(g.pi_schema, PI_SCHEMA_V2);
+    assert_eq!(g.player1_score, -1);
+    assert_eq!(g.loot_mask, 0b11);
+}
+
+#[test]
+fn submit_turn_rejects_score_above_loot_delta_under_v1() {
+    let session_id = 108u32;
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let player1 = Address::generate(&env);
+    let player2 = Address::generate(&env);
+    let hub_id = env.register(MockHubContract, ());
+    let verifier_id = env.register(MockVerifierContract, ());
+    let heist_id = env.register(HeistContract, (admin, hub_id, verifier_id));
+    let heist = HeistContractClient::new(&env, &heist_id);
+    heist.set_pi_schema(&PI_SCHEMA_V1);
+
+    let s1 = BytesN::from_array(&env, &[1u8; 32]);
+    let s2 = BytesN::from_array(&env, &[2u8; 32]);
+    let mc = make_commit(&env, &BytesN::from_array(&env, &[0xAAu8; 32]));
+    heist.start_game(
+        &session_id, &player1, &player2, &50, &50,
+        &make_commit(&env, &s1), &make_commit(&env, &s2), &mc, &mc, &default_config(),
+    );
+    heist.reveal_seed(&session_id, &player1, &s1);
+    heist.reveal_seed(&session_id, &player2, &s2);
+    let map_commitment = BytesN::from_array(&env, &[0x11u8; 32]);
+    let p1_pos_commit = BytesN::from_array(&env, &[0x22u8; 32]);
+    let p2_pos_commit = BytesN::from_array(&env, &[0x23u8; 32]);
+    heist.begin_match(&session_id, &map_commitment, &p1_pos_commit, &p2_pos_commit);
+    assert_eq!(heist.get_game(&session_id).pi_schema, PI_SCHEMA_V1);
+
+    let v1_pi_hash = |public_turn: &TurnZkPublic| {
+        env.as_contract(&heist_id, || {
+            compute_turn_pi_hash(&env, public_turn, 1)
+        })
+    };
+
+    // v1 proofs don't bind hazards, but score_delta still can't exceed loot_delta.
+    let inflated = first_turn(&env, &heist_id, session_id, &player1, 5, 1, 1);
+    let proof_blob = make_test_proof_blob(&env, &v1_pi_hash(&inflated));
+    let res = heist.try_submit_turn(&session_id, &player1, &proof_blob, &inflated);
+    assert_eq!(res, Err(Ok(Error::InvalidScoreDelta)));
+
+    let honest = first_turn(&env, &heist_id, session_id, &player1, 0, 1, 1);
+    let proof_blob = make_test_proof_blob(&env, &v1_pi_hash(&honest));
+    heist.submit_turn(&session_id, &player1, &proof_blob, &honest);
+    assert_eq!(heist.get_game(&session_id).player1_score, 0);
+}
+
+/// A game written by the first release, in the middle of player 1's turn 0.
+fn legacy_active_game(
+    env: &Env,
+    player1: &Address,
+    player2: &Address,
+    session_seed: &BytesN<32>,
+    map_commitment: &BytesN<32>,
+    p1_pos_commit: &BytesN<32>,
+    p2_pos_commit: &BytesN<32>,
+    now: u64,
+) -> LegacyGame {
+    let zero32 = BytesN::from_array(env, &[0u8; 32]);
+    LegacyGame {
+        player1: player1.clone(),
+        player2: player2.clone(),
+        player1_points: 50,
+        player2_points: 50,
+        status: GameStatus::Active,
+        p1_seed_commit: zero32.clone(),
+        p2_seed_commit: zero32.clone(),
+        p1_seed_reveal: zero32.clone(),
+        p2_seed_reveal: zero32.clone(),
+        session_seed: session_seed.clone(),
+        p1_map_seed_commit: zero32.clone(),
+        p2_map_seed_commit: zero32.clone(),
+        map_commitment: map_commitment.clone(),
+        player1_pos_commit: p1_pos_commit.clone(),
+        player2_pos_commit: p2_pos_commit.clone(),
+        player1_score: 0,
+        player2_score: 0,
+        loot_total_collected: 0,
+        loot_mask: 0,
+        state_commitment: BytesN::from_array(env, &[0x44u8; 32]),
+        started_at_ts: now,
+        turn_index: 0,
+        active_player: player1.clone(),
+        winner: None,
+        last_proof_id: zero32,
+        p1_time_remaining: PLAYER_TIME_SECONDS,
+        p2_time_remaining: PLAYER_TIME_SECONDS,
+        last_turn_start_ts: now,
+        player1_exited: false,
+        player2_exited: false,
+        p1_exit_turn: u64::MAX,
+        p2_exit_turn: u64::MAX,
+    }
+}
+
+#[test]
+fn game_stored_by_the_first_release_is_migrated_and_keeps_playing() {
+    use soroban_sdk::testutils::Ledger;
+
+    let session_id = 136u32;
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|l| l.timestamp = 1_000);
+
+    let admin = Address::generate(&env);
+    let player1 = Address::generate(&env);
+    let player2 = Address::generate(&env);
+    let hub_id = env.register(MockHubContract, ());
+    let verifier_id = env.register(MockVerifierContract, ());
+    let verifier = MockVerifierContractClient::new(&env, &verifier_id);
+    let old_vk = BytesN::from_array(&env, &[0x01u8; 32]);
+    verifier.set_default_vk(&old_vk);
+    let heist_id = env.register(HeistContract, (admin, hub_id, verifier_id));
+    let heist = HeistContractClient::new(&env, &heist_id);
+    // The deployment has since moved on; the old game keeps the v1 circuit.
+    heist.set_pi_schema(&PI_SCHEMA_V2);
+
+    let session_seed = BytesN::from_array(&env, &[0x55u8; 32]);
+    let map_commitment = BytesN::from_array(&env, &[0x11u8; 32]);
+    let p1_pos_commit = BytesN::from_array(&env, &[0x22u8; 32]);
+    let p2_pos_commit = BytesN::from_array(&env, &[0x23u8; 32]);
+    let legacy = legacy_active_game(
+        &env, &player1, &player2, &session_seed, &map_commitment, &p1_pos_commit,
+        &p2_pos_commit, 1_000,
+    );
+    env.as_contract(&heist_id, || {
+        env.storage().temporary().set(&DataKey::Game(session_id), &legacy);
+    });
+
+    let is_migrated = || {
+        env.as_contract(&heist_id, || {
+            let storage = env.storage().temporary();
+            !storage.has(&DataKey::Game(session_id)) && storage.has(&DataKey::GameV2(session_id))
+        })
+    };
+
+    // Views convert the game in memory only.
+    let g = heist.get_game_view(&session_id);
+    assert_eq!(g.status, GameStatus::Active);
+    assert_eq!(g.pi_schema, PI_SCHEMA_V1);
+    assert_eq!(g.vk_id, Some(old_vk.clone()));
+    assert_eq!(g.config, default_config());
+    assert_eq!(g.state_commitment, BytesN::from_array(&env, &[0x44u8; 32]));
+    heist.get_game(&session_id);
+    heist.get_player_view(&session_id, &player1);
+    assert!(!is_migrated());
+
+    // The first turn moves it over, pinning the verifier's key of the moment.
+    let pi_hash_for = |turn: &TurnZkPublic, player_tag: u32| {
+        env.as_contract(&heist_id, || compute_turn_pi_hash(&env, turn, player_tag))
+    };
+    let turn0 = first_turn(&env, &heist_id, session_id, &player1, 1, 1, 0b1);
+    let blob = make_test_proof_blob(&env, &pi_hash_for(&turn0, 1));
+    heist.submit_turn(&session_id, &player1, &blob, &turn0);
+    assert!(is_migrated());
+    assert_eq!(verifier.last_vk(), Some(old_vk.clone()));
+    let g = heist.get_game(&session_id);
+    assert_eq!(g.turn_index, 1);
+    assert_eq!(g.player1_score, 1);
+    assert_eq!(g.state_commitment, turn0.state_commit_after);
+
+    // A new circuit's key becomes the default after the game was migrated.
+    verifier.set_default_vk(&BytesN::from_array(&env, &[0x02u8; 32]));
+    let turn1 = player2_idle_turn(&env, &heist_id, session_id, &player2);
+    let blob = make_test_proof_blob(&env, &pi_hash_for(&turn1, 2));
+    heist.submit_turn(&session_id, &player2, &blob, &turn1);
+    assert_eq!(verifier.last_vk(), Some(old_vk));
+    assert_eq!(heist.get_game(&session_id).turn_index, 2);
+}
+
+#[test]
+fn legacy_game_waiting_on_reveals_gets_a_reveal_window_and_the_default_schema() {
+    use soroban_sdk::testutils::Ledger;
+
+    let session_id = 137u32;
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|l| l.timestamp = 1_000);
+
+    let admin = Address::generate(&env);
+    let player1 = Address::generate(&env);
+    let player2 = Address::generate(&env);
+    let hub_id = env.register(MockHubContract, ());
+    let verifier_id = env.register(MockVerifierContract, ());
+    let heist_id = env.register(HeistContract, (admin, hub_id, verifier_id));
+    let heist = HeistContractClient::new(&env, &heist_id);
+
+    let s1 = BytesN::from_array(&env, &[1u8; 32]);
+    let s2 = BytesN::from_array(&env, &[2u8; 32]);
+    let zero32 = BytesN::from_array(&env, &[0u8; 32]);
+    let mut legacy = legacy_active_game(
+        &env, &player1, &player2, &zero32, &zero32, &zero32, &zero32, 0,
+    );
+    legacy.status = GameStatus::WaitingReveal;
+    legacy.p1_seed_commit = make_commit(&env, &s1);
+    legacy.p2_seed_commit = make_commit(&env, &s2);
+    env.as_contract(&heist_id, || {
+        env.storage().temporary().set(&DataKey::Game(session_id), &legacy);
+    });
+
+    let g = heist.get_game(&session_id);
+    assert_eq!(g.pi_schema, 0);
+    assert_eq!(g.reveal_deadline_ts, 1_000 + REVEAL_TIMEOUT_SECONDS);
+
+    heist.reveal_seed(&session_id, &player1, &s1);
+    heist.reveal_seed(&session_id, &player2, &s2);
+    heist.begin_match(
+        &session_id,
+        &BytesN::from_array(&env, &[0x11u8; 32]),
+        &BytesN::from_array(&env, &[0x22u8; 32]),
+        &BytesN::from_array(&env, &[0x23u8; 32]),
+    );
+    assert_eq!(heist.get_game(&session_id).pi_schema, PI_SCHEMA_DEFAULT);
+}
+
+#[test]
+fn schema_v2_binds_the_loot_mask_into_pi_hash() {
+    let session_id = 135u32;
+    let (env, player1, player2, heist, _hub, s1, s2) = setup_started_game(session_id);
+    heist.set_pi_schema(&PI_SCHEMA_V2);
+    heist.reveal_seed(&session_id, &player1, &s1);
+    heist.reveal_seed(&session_id, &player2, &s2);
+    let map_commitment = BytesN::from_array(&env, &[0x11u8; 32]);
+    let p1_pos_commit = BytesN::from_array(&env, &[0x22u8; 32]);
+    let p2_pos_commit = BytesN::from_array(&env, &[0x23u8; 32]);
+    heist.begin_match(&session_id, &map_commitment, &p1_pos_commit, &p2_pos_commit);
+    assert_eq!(heist.get_game_view(&session_id).pi_schema, PI_SCHEMA_V2);
+
+    let mut turn0 = first_turn(&env, &heist.address, session_id, &player1, 1, 1, 0b1);
+    let (v1_pi_hash, v2_pi_hash) = env.as_contract(&heist.address, || {
+        (compute_turn_pi_hash(&env, &turn0, 1), compute_turn_pi_hash_v2(&env, &turn0, 1))
+    });
+
+    // A v1 proof of the same turn leaves the mask unbound.
+    assert_eq!(
+        heist.try_submit_turn(&session_id, &player1, &make_test_proof_blob(&env, &v1_pi_hash), &turn0),
+        Err(Ok(Error::InvalidPublicInput))
+    );
+
+    // The proof covers cell 0; claiming cell 5 instead is rejected.
+    let blob = make_test_proof_blob(&env, &v2_pi_hash);
+    turn0.loot_mask = 1i128 << 5;
+    assert_eq!(
+        heist.try_submit_turn(&session_id, &player1, &blob, &turn0),
+        Err(Ok(Error::InvalidPublicInput))
+    );
+
+    turn0.loot_mask = 0b1;
+    heist.submit_turn(&session_id, &player1, &blob, &turn0);
+    assert_eq!(heist.get_game_view(&session_id).loot_mask, 0b1);
+}
+
+#[test]
+fn game_started_under_old_schema_keeps_verifying_and_can_migrate() {
+    let session_id = 111u32;
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let player1 = Address::generate(&env);
+    let player2 = Address::generate(&env);
+    let hub_id = env.register(MockHubContract, ());
+    let verifier_id = env.register(MockVerifierContract, ());
+    let heist_id = env.register(HeistContract, (admin, hub_id, verifier_id));
+    let heist = HeistContractClient::new(&env, &heist_id);
+    heist.set_pi_schema(&PI_SCHEMA_V1);
+
+    let s1 = BytesN::from_array(&env, &[1u8; 32]);
+    let s2 = BytesN::from_array(&env, &[2u8; 32]);
+    let mc = make_commit(&env, &BytesN::from_array(&env, &[0xAAu8; 32]));
+    heist.start_game(
+        &session_id, &player1, &player2, &50, &50,
+        &make_commit(&env, &s1), &make_commit(&env, &s2), &mc, &mc, &default_config(),
+    );
+    heist.reveal_seed(&session_id, &player1, &s1);
+    heist.reveal_seed(&session_id, &player2, &s2);
+    let map_commitment = BytesN::from_array(&env, &[0x11u8; 32]);
+    let p1_pos_commit = BytesN::from_array(&env, &[0x22u8; 32]);
+    let p2_pos_commit = BytesN::from_array(&env, &[0x23u8; 32]);
+    heist.begin_match(&session_id, &map_commitment, &p1_pos_commit, &p2_pos_commit);
+
+    // The deployment moves on to v2; the in-flight game stays on v1.
+    heist.set_pi_schema(&PI_SCHEMA_V2);
+    assert_eq!(heist.get_game(&session_id).pi_schema, PI_SCHEMA_V1);
+
+    let public_turn = first_turn(&env, &heist_id, session_id, &player1, 0, 0, 0);
+    let v1_pi_hash = env.as_contract(&heist_id, || compute_turn_pi_hash(&env, &public_turn, 1));
+    let v1_blob = make_test_proof_blob(&env, &v1_pi_hash);
+    heist.submit_turn(&session_id, &player1, &v1_blob, &public_turn);
+    assert_eq!(heist.get_game(&session_id).turn_index, 1);
+
+    // Both players opt in to the loot-mask-bound format for the rest of the game.
+    heist.migrate_pi_schema(&session_id);
+    assert_eq!(heist.get_game(&session_id).pi_schema, PI_SCHEMA_V2);
+    assert_eq!(
+        heist.try_migrate_pi_schema(&session_id),
+        Err(Ok(Error::UnsupportedPiSchema))
+    );
+}
+
+fn setup_started_game(
+    session_id: u32,
+) -> (
+    Env,
+    Address, // player1
+    Address, // player2
+    HeistContractClient<'static>,
+    MockHubContractClient<'static>,
+    BytesN<32>, // p1 seed secret
+    BytesN<32>, // p2 seed secret
+) {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let player1 = Address::generate(&env);
+    let player2 = Address::generate(&env);
+    let hub_id = env.register(MockHubContract, ());
+    let hub = MockHubContractClient::new(&env, &hub_id);
+    let verifier_id = env.register(MockVerifierContract, ());
+    let heist_id = env.register(HeistContract, (admin, hub_id, verifier_id));
+    let heist = HeistContractClient::new(&env, &heist_id);
+    // Turn helpers below prove the loot-mask-bound schema, not the v1 default.
+    heist.set_pi_schema(&PI_SCHEMA_V2);
+
+    let s1 = BytesN::from_array(&env, &[1u8; 32]);
+    let s2 = BytesN::from_array(&env, &[2u8; 32]);
+    let mc = make_commit(&env, &BytesN::from_array(&env, &[0xAAu8; 32]));
+    heist.start_game(
+        &session_id, &player1, &player2, &50, &50,
+        &make_commit(&env, &s1), &make_commit(&env, &s2), &mc, &mc, &default_config(),
+    );
+    (env, player1, player2, heist, hub, s1, s2)
+}
+
+#[test]
+fn reveal_timeout_credits_the_player_who_revealed() {
+    use soroban_sdk::testutils::Ledger;
+
+    let session_id = 112u32;
+    let (env, _player1, player2, heist, hub, _s1, s2) = setup_started_game(session_id);
+    heist.reveal_seed(&session_id, &player2, &s2);
+
+    let deadline = heist.get_game(&session_id).reveal_deadline_ts;
+    env.ledger().with_mut(|li| li.timestamp = deadline - 1);
+    assert_eq!(
+        heist.try_claim_reveal_timeout(&session_id),
+        Err(Ok(Error::RevealWindowOpen))
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = deadline);
+    heist.claim_reveal_timeout(&session_id);
+
+    let g = heist.get_game(&session_id);
+    assert_eq!(g.status, GameStatus::Ended);
+    assert_eq!(g.winner, Some(player2));
+    assert!(hub.ended(&session_id));
+    assert!(!hub.player1_won(&session_id));
+    assert_eq!(
+        heist.try_claim_reveal_timeout(&session_id),
+        Err(Ok(Error::InvalidStatus))
+    );
+}
+
+#[test]
+fn reveal_timeout_draws_when_nobody_revealed() {
+    use soroban_sdk::testutils::Ledger;
+
+    let session_id = 113u32;
+    let (env, _player1, _player2, heist, hub, _s1, _s2) = setup_started_game(session_id);
+
+    env.ledger().with_mut(|li| li.timestamp += 601);
+    heist.claim_reveal_timeout(&session_id);
+    let draw = GameOutcome::Draw(EndReason::RevealTimeout);
+    assert!(result_not_reported(&env, &heist.address, session_id, draw.clone()));
+
+    let g = heist.get_game(&session_id);
+    assert_eq!(g.status, GameStatus::Ended);
+    assert_eq!(g.winner, None);
+    assert_eq!(g.outcome, draw);
+    assert!(!hub.ended(&session_id));
+}
+
+#[test]
+fn escrow_pays_winner_minus_fee_and_refunds_cancelled_games() {
+    use soroban_sdk::testutils::Ledger;
+    use soroban_sdk::token::{StellarAssetClient, TokenClient};
+
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let player1 = Address::generate(&env);
+    let player2 = Address::generate(&env);
+    let hub_id = env.register(MockHubContract, ());
+    let hub = MockHubContractClient::new(&env, &hub_id);
+    let verifier_id = env.register(MockVerifierContract, ());
+    let heist_id = env.register(HeistContract, (admin.clone(), hub_id, verifier_id));
+    let heist = HeistContractClient::new(&env, &heist_id);
+
+    let sac = env.register_stellar_asset_contract_v2(admin.clone());
+    let token = TokenClient::new(&env, &sac.address());
+    StellarAssetClient::new(&env, &sac.address()).mint(&player1, &1_000);
+    StellarAssetClient::new(&env, &sac.address()).mint(&player2, &1_000);
+
+    heist.set_stake_token(&Some(sac.address()));
+    assert_eq!(heist.try_set_fee_bps(&10_001), Err(Ok(Error::InvalidFee)));
+    heist.set_fee_bps(&500); // 5%
+
+    let s1 = BytesN::from_array(&env, &[1u8; 32]);
+    let s2 = BytesN::from_array(&env, &[2u8; 32]);
+    let mc = make_commit(&env, &BytesN::from_array(&env, &[0xAAu8; 32]));
+
+    // Stakes must be non-negative, and the fee on their sum must not overflow.
+    for (p1_points, p2_points) in [(-1, 0), (i128::MAX, 1), (i128::MAX / 10_000, 1)] {
+        assert_eq!(
+            heist.try_start_game(
+                &114, &player1, &player2, &p1_points, &p2_points,
+                &make_commit(&env, &s1), &make_commit(&env, &s2), &mc, &mc, &default_config(),
+            ),
+            Err(Ok(Error::InvalidStake))
+        );
+    }
+
+    // Game 1: player2 reveals, player1 never does → player2 takes the pot.
+    heist.start_game(
+        &114, &player1, &player2, &100, &300,
+        &make_commit(&env, &s1), &make_commit(&env, &s2), &mc, &mc, &default_config(),
+    );
+    assert_eq!(token.balance(&heist_id), 400);
+    assert_eq!(token.balance(&player1), 900);
+    assert_eq!(token.balance(&player2), 700);
+
+    heist.reveal_seed(&114, &player2, &s2);
+    env.ledger().with_mut(|li| li.timestamp += 601);
+    heist.claim_reveal_timeout(&114);
+
+    assert_eq!(token.balance(&heist_id), 0);
+    assert_eq!(token.balance(&admin), 20);
+    assert_eq!(token.balance(&player2), 700 + 380);
+
+    // Game 2: nobody reveals → both stakes are returned, no fee.
+    heist.start_game(
+        &115, &player1, &player2, &50, &50,
+        &make_commit(&env, &s1), &make_commit(&env, &s2), &mc, &mc, &default_config(),
+    );
+    env.ledger().with_mut(|li| li.timestamp += 601);
+    heist.claim_reveal_timeout(&115);
+    // Like any other draw, the cancelled game can't be reported to the hub.
+    let draw = GameOutcome::Draw(EndReason::RevealTimeout);
+    assert!(result_not_reported(&env, &heist_id, 115, draw));
+    assert!(!hub.ended(&115));
+
+    assert_eq!(token.balance(&heist_id), 0);
+    assert_eq!(token.balance(&admin), 20);
+    assert_eq!(token.balance(&player1), 900);
+    assert_eq!(token.balance(&player2), 1_080);
+}
+
+#[test]
+fn resign_before_match_begins_awards_the_opponent() {
+    let session_id = 116u32;
+    let (env, player1, player2, heist, hub, s1, _s2) = setup_started_game(session_id);
+    heist.reveal_seed(&session_id, &player1, &s1);
+
+    let outsider = Address::generate(&env);
+    assert_eq!(
+        heist.try_resign(&session_id, &outsider),
+        Err(Ok(Error::NotPlayer))
+    );
+
+    heist.resign(&session_id, &player2);
+
+    let g = heist.get_game(&session_id);
+    assert_eq!(g.status, GameStatus::Ended);
+    assert_eq!(g.winner, Some(player1.clone()));
+    assert!(hub.ended(&session_id));
+    assert!(hub.player1_won(&session_id));
+    assert_eq!(
+        heist.try_resign(&session_id, &player1),
+        Err(Ok(Error::GameAlreadyEnded))
+    );
+}
+
+#[test]
+fn resign_mid_match_emits_ended_with_resignation_reason() {
+    use soroban_sdk::{testutils::Events, Event};
+
+    let session_id = 117u32;
+    let (env, player1, player2, heist_id, hub, _seed, _map, _p1_pos, _p2_pos) =
+        setup_active_game(session_id);
+    let heist = HeistContractClient::new(&env, &heist_id);
+
+    heist.resign(&session_id, &player1);
+
+    let ended = GameEnded {
+        session_id,
+        player1_score: 0,
+        player2_score: 0,
+        winner: Some(player2.clone()),
+        outcome: GameOutcome::P2Win(EndReason::Resignation),
+    };
+    assert_eq!(env.events().all(), [ended.to_xdr(&env, &heist_id)]);
+    let g = heist.get_game(&session_id);
+    assert_eq!(g.status, GameStatus::Ended);
+    assert_eq!(g.winner, Some(player2));
+    assert!(hub.ended(&session_id));
+    assert!(!hub.player1_won(&session_id));
+}
+
+#[test]
+fn failed_hub_call_is_published_instead_of_dropped() {
+    let session_id = 139u32;
+    let (env, player1, player2, heist_id, _hub, _seed, _map, _p1_pos, _p2_pos) =
+        setup_active_game(session_id);
+    let heist = HeistContractClient::new(&env, &heist_id);
+    heist.set_hub(&env.register(MockFailingHubContract, ()));
+
+    // The game still ends; the hub failure shows up as an event.
+    heist.resign(&session_id, &player1);
+    let win = GameOutcome::P2Win(EndReason::Resignation);
+    assert!(result_not_reported(&env, &heist_id, session_id, win));
+    assert_eq!(heist.get_game(&session_id).winner, Some(player2));
+}
+
+#[test]
+fn accepted_draw_offer_ends_game_as_draw() {
+    let session_id = 118u32;
+    let (env, player1, player2, heist_id, hub, _seed, _map, _p1_pos, _p2_pos) =
+        setup_active_game(session_id);
+    let heist = HeistContractClient::new(&env, &heist_id);
+
+    assert_eq!(
+        heist.try_accept_draw(&session_id, &player2),
+        Err(Ok(Error::NoDrawOffer))
+    );
+    heist.offer_draw(&session_id, &player1);
+    // The offering player cannot accept their own offer.
+    assert_eq!(
+        heist.try_accept_draw(&session_id, &player1),
+        Err(Ok(Error::NoDrawOffer))
+    );
+    heist.accept_draw(&session_id, &player2);
+    let draw = GameOutcome::Draw(EndReason::Agreement);
+    assert!(result_not_reported(&env, &heist_id, session_id, draw.clone()));
+
+    let g = heist.get_game(&session_id);
+    assert_eq!(g.status, GameStatus::Ended);
+    assert_eq!(g.winner, None);
+    assert_eq!(g.outcome, draw);
+    assert!(!hub.ended(&session_id));
+}
+
+#[test]
+fn draw_offer_lapses_when_opponent_plays_on() {
+    let session_id = 119u32;
+    let (env, player1, player2, heist_id, _hub, _session_seed, _map_commitment, _p1_pos, _p2_pos) =
+        setup_active_game(session_id);
+    let heist = HeistContractClient::new(&env, &heist_id);
+
+    heist.offer_draw(&session_id, &player2);
+    let public_turn = first_turn(&env, &heist_id, session_id, &player1, 0, 0, 0);
+    let pi_hash = turn_pi_hash(&env, &heist_id, &public_turn, 1);
+    heist.submit_turn(&session_id, &player1, &make_test_proof_blob(&env, &pi_hash), &public_turn);
+
+    assert_eq!(heist.get_game(&session_id).draw_offered_by, None);
+    assert_eq!(
+        heist.try_accept_draw(&session_id, &player1),
+        Err(Ok(Error::NoDrawOffer))
+    );
+}
+
+#[test]
+fn game_and_player_views_need_no_auth() {
+    use soroban_sdk::testutils::Ledger;
+
+    let session_id = 120u32;
+    let (env, player1, player2, heist_id, _hub, _seed, map_commitment, _p1_pos, _p2_pos) =
+        setup_active_game(session_id);
+    let heist = HeistContractClient::new(&env, &heist_id);
+
+    env.set_auths(&[]);
+    assert!(heist.try_get_game(&session_id).is_err());
+    assert_eq!(heist.get_game_view(&session_id).map_commitment, map_commitment);
+
+    env.ledger().with_mut(|li| li.timestamp += 40);
+
+    let p1_view = heist.get_player_view(&session_id, &player1);
+    assert_eq!(p1_view.player_tag, 1);
+    assert_eq!(p1_view.opponent, player2);
+    assert!(p1_view.is_my_turn);
+    assert_eq!(p1_view.my_time_remaining, PLAYER_TIME_SECONDS - 40);
+    assert_eq!(p1_view.opponent_time_remaining, PLAYER_TIME_SECONDS);
+    // The stored clock is only charged when a turn lands.
+    assert_eq!(p1_view.game.p1_time_remaining, PLAYER_TIME_SECONDS);
+
+    let p2_view = heist.get_player_view(&session_id, &player2);
+    assert_eq!(p2_view.player_tag, 2);
+    assert!(!p2_view.is_my_turn);
+    assert_eq!(p2_view.my_time_remaining, PLAYER_TIME_SECONDS);
+    assert_eq!(p2_view.opponent_time_remaining, PLAYER_TIME_SECONDS - 40);
+
+    let outsider = Address::generate(&env);
+    assert!(matches!(
+        heist.try_get_player_view(&session_id, &outsider),
+        Err(Ok(Error::NotPlayer))
+    ));
+}
+
+#[test]
+fn match_result_outlives_the_temporary_game_entry() {
+    use soroban_sdk::testutils::Ledger;
+
+    let session_id = 121u32;
+    let (env, player1, player2, heist_id, _hub, _session_seed, _map_commitment, _p1_pos, _p2_pos) =
+        setup_active_game(session_id);
+    let heist = HeistContractClient::new(&env, &heist_id);
+
+    assert!(heist.try_get_match_result(&session_id).is_err());
+
+    let public_turn = first_turn(&env, &heist_id, session_id, &player1, 1, 1, 0b1);
+    let pi_hash = turn_pi_hash(&env, &heist_id, &public_turn, 1);
+    let proof_blob = make_test_proof_blob(&env, &pi_hash);
+    heist.submit_turn(&session_id, &player1, &proof_blob, &public_turn);
+    heist.resign(&session_id, &player2);
+
+    // Let the temporary game entry expire.
+    env.ledger().with_mut(|li| li.sequence_number += 600_000);
+    assert!(heist.try_get_game_view(&session_id).is_err());
+
+    let result = heist.get_match_result(&session_id);
+    assert_eq!(result.player1, player1);
+    assert_eq!(result.player2, player2);
+    assert_eq!(result.player1_score, 1);
+    assert_eq!(result.player2_score, 0);
+    assert_eq!(result.winner, Some(player1));
+    assert_eq!(result.outcome, GameOutcome::P1Win(EndReason::Resignation));
+    assert_eq!(result.turn_count, 1);
+    assert_eq!(result.final_state_commitment, public_turn.state_commit_after);
+    assert_eq!(
+        result.last_proof_id,
+        BytesN::from(env.crypto().keccak256(&proof_blob))
+    );
+}
+
+#[test]
+fn create_game_allocates_ids_from_the_reserved_range() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let player1 = Address::generate(&env);
+    let player2 = Address::generate(&env);
+    let hub_id = env.register(MockHubContract, ());
+    let verifier_id = env.register(MockVerifierContract, ());
+    let heist_id = env.register(HeistContract, (admin, hub_id, verifier_id));
+    let heist = HeistContractClient::new(&env, &heist_id);
+
+    let s1 = BytesN::from_array(&env, &[1u8; 32]);
+    let s2 = BytesN::from_array(&env, &[2u8; 32]);
+    let c1 = make_commit(&env, &s1);
+    let c2 = make_commit(&env, &s2);
+    let mc = make_commit(&env, &BytesN::from_array(&env, &[0xAAu8; 32]));
+
+    // Caller-chosen ids live below the high bit and don't affect allocation.
+    heist.start_game(&1, &player1, &player2, &50, &50, &c1, &c2, &mc, &mc, &default_config());
+
+    let first = heist.create_game(&player1, &player2, &50, &50, &c1, &c2, &mc, &mc, &default_config());
+    let second = heist.create_game(&player1, &player2, &50, &50, &c1, &c2, &mc, &mc, &default_config());
+    assert_eq!(first, 1 << 31);
+    assert_eq!(second, (1 << 31) + 1);
+
+    let g = heist.get_game_view(&second);
+    assert_eq!(g.status, GameStatus::WaitingReveal);
+    assert_eq!(g.player1, player1);
+
+    // start_game cannot squat the reserved range, taken or not.
+    for session_id in [second, second + 1, u32::MAX] {
+        assert_eq!(
+            heist.try_start_game(&session_id, &player1, &player2, &50, &50, &c1, &c2, &mc, &mc, &default_config()),
+            Err(Ok(Error::ReservedSessionId))
+        );
+    }
+
+    // Once the range is used up, allocation fails instead of wrapping.
+    env.as_contract(&heist_id, || {
+        env.storage().instance().set(&DataKey::NextSessionId, &(1u32 << 31));
+    });
+    assert_eq!(
+        heist.try_create_game(&player1, &player2, &50, &50, &c1, &c2, &mc, &mc, &default_config()),
+        Err(Ok(Error::SessionIdsExhausted))
+    );
+}
+
+#[test]
+fn submit_turn_appends_paginated_turn_history() {
+    use soroban_sdk::testutils::Ledger;
+
+    let session_id = 122u32;
+    let (env, player1, player2, heist_id, _hub, _session_seed, _map_commitment, _p1_pos, _p2_pos) =
+        setup_active_game(session_id);
+    let heist = HeistContractClient::new(&env, &heist_id);
+    let start_ts = env.ledger().timestamp();
+
+    env.ledger().with_mut(|li| li.timestamp += 7);
+    let turn0 = first_turn(&env, &heist_id, session_id, &player1, 1, 1, 0b1);
+    let blob0 = make_test_proof_blob(&env, &turn_pi_hash(&env, &heist_id, &turn0, 1));
+    heist.submit_turn(&session_id, &player1, &blob0, &turn0);
+
+    // Player2 stays in place and collects nothing.
+    env.ledger().with_mut(|li| li.timestamp += 12);
+    let turn1 = player2_idle_turn(&env, &heist_id, session_id, &player2);
+    let blob1 = make_test_proof_blob(&env, &turn_pi_hash(&env, &heist_id, &turn1, 2));
+    heist.submit_turn(&session_id, &player2, &blob1, &turn1);
+
+    let all = heist.get_turns(&session_id, &0, &10);
+    assert_eq!(all.len(), 2);
+    let first = all.get(0).unwrap();
+    assert_eq!(first.turn_index, 0);
+    assert_eq!(first.player_tag, 1);
+    assert_eq!(first.pos_commit_after, turn0.pos_commit_after);
+    assert_eq!(first.score_delta, 1);
+    assert_eq!(first.loot_mask, 0b1);
+    assert_eq!(first.proof_id, BytesN::from(env.crypto().keccak256(&blob0)));
+    assert_eq!(first.timestamp, start_ts + 7);
+    assert_eq!(first.elapsed, 7);
+    let second = all.get(1).unwrap();
+    assert_eq!(second.player_tag, 2);
+    assert_eq!(second.elapsed, 12);
+
+    let page = heist.get_turns(&session_id, &1, &1);
+    assert_eq!(page.len(), 1);
+    assert_eq!(page.get(0).unwrap().turn_index, 1);
+    assert_eq!(heist.get_turns(&session_id, &2, &10).len(), 0);
+    assert_eq!(heist.get_turns(&session_id, &0, &0).len(), 0);
+}
+
+#[test]
+fn start_game_rejects_invalid_config() {
+    let (env, player1, player2, heist, _hub, s1, s2) = setup_started_game(123);
+    let c1 = make_commit(&env, &s1);
+    let c2 = make_commit(&env, &s2);
+    let mc = make_commit(&env, &BytesN::from_array(&env, &[0xAAu8; 32]));
+
+    let no_time = GameConfig { time_per_player: 0, ..default_config() };
+    let too_wide = GameConfig { board_width: BOARD_SIDE + 1, ..default_config() };
+    let too_much_loot = GameConfig {
+        board_width: 4,
+        board_height: 4,
+        loot_count: 17,
+        ..default_config()
+    };
+    // width × height would overflow u32.
+    let huge_board = GameConfig {
+        board_width: u32::MAX,
+        board_height: u32::MAX,
+        ..default_config()
+    };
+    for config in [no_time, too_wide, too_much_loot, huge_board] {
+        assert_eq!(
+            heist.try_start_game(&124, &player1, &player2, &50, &50, &c1, &c2, &mc, &mc, &config),
+            Err(Ok(Error::InvalidConfig))
+        );
+    }
+}
+
+#[test]
+fn game_config_drives_clocks_loot_and_state_commitment() {
+    use soroban_sdk::testutils::Ledger;
+
+    let session_id = 125u32;
+    let blitz = GameConfig {
+        time_per_player: 60,
+        clock_mode: ClockMode::Fischer,
+        increment_seconds: 5,
+        max_turns: 0,
+        loot_count: 0,
+        board_width: 8,
+        board_height: 8,
+    };
+    let (env, player1, _player2, heist_id, _hub, _session_seed, _map_commitment, _p1_pos, _p2_pos) =
+        setup_active_game_with_config(session_id, blitz.clone());
+    let heist = HeistContractClient::new(&env, &heist_id);
+
+    let g = heist.get_game_view(&session_id);
+    assert_eq!(g.config, blitz);
+    assert_eq!(g.p1_time_remaining, 60);
+    assert_eq!(g.p2_time_remaining, 60);
+    let mut default_rules = stored_game(&env, &heist_id, session_id);
+    default_rules.config = default_config();
+    let default_rules_commitment = env.as_contract(&heist_id, || {
+        compute_state_commitment(&env, session_id, &default_rules)
+    });
+    assert_ne!(g.state_commitment, default_rules_commitment);
+
+    env.ledger().with_mut(|li| li.timestamp += 10);
+
+    // No loot on this board: any collected cell exceeds loot_count.
+    let greedy = first_turn(&env, &heist_id, session_id, &player1, 1, 1, 0b1);
+    let pi_hash = turn_pi_hash(&env, &heist_id, &greedy, 1);
+    assert_eq!(
+        heist.try_submit_turn(&session_id, &player1, &make_test_proof_blob(&env, &pi_hash), &greedy),
+        Err(Ok(Error::InvalidTurnData))
+    );
+
+    let public_turn = first_turn(&env, &heist_id, session_id, &player1, 0, 0, 0);
+    let pi_hash = turn_pi_hash(&env, &heist_id, &public_turn, 1);
+    heist.submit_turn(&session_id, &player1, &make_test_proof_blob(&env, &pi_hash), &public_turn);
+
+    // 60 − 10 elapsed + 5 increment.
+    assert_eq!(heist.get_game_view(&session_id).p1_time_remaining, 55);
+}
+
+#[test]
+fn bronstein_delay_refunds_at_most_the_time_used() {
+    use soroban_sdk::testutils::Ledger;
+
+    let session_id = 126u32;
+    let config = GameConfig {
+        time_per_player: 60,
+        clock_mode: ClockMode::Bronstein,
+        increment_seconds: 10,
+        ..default_config()
+    };
+    let (env, player1, player2, heist_id, _hub, _session_seed, _map_commitment, _p1_pos, _p2_pos) =
+        setup_active_game_with_config(session_id, config);
+    let heist = HeistContractClient::new(&env, &heist_id);
+
+    // A quick move is refunded in full (Fischer would have left 66).
+    env.ledger().with_mut(|li| li.timestamp += 4);
+    let turn0 = first_turn(&env, &heist_id, session_id, &player1, 0, 0, 0);
+    let pi_hash = turn_pi_hash(&env, &heist_id, &turn0, 1);
+    heist.submit_turn(&session_id, &player1, &make_test_proof_blob(&env, &pi_hash), &turn0);
+    assert_eq!(heist.get_game_view(&session_id).p1_time_remaining, 60);
+
+    // A slow move only gets the capped delay back.
+    env.ledger().with_mut(|li| li.timestamp += 25);
+    let turn1 = player2_idle_turn(&env, &heist_id, session_id, &player2);
+    let pi_hash = turn_pi_hash(&env, &heist_id, &turn1, 2);
+    heist.submit_turn(&session_id, &player2, &make_test_proof_blob(&env, &pi_hash), &turn1);
+    assert_eq!(heist.get_game_view(&session_id).p2_time_remaining, 60 - 25 + 10);
+}
+
+#[test]
+fn turn_landing_one_second_before_the_flag_is_accepted() {
+    use soroban_sdk::testutils::Ledger;
+
+    let session_id = 127u32;
+    let config = GameConfig {
+        time_per_player: 30,
+        increment_seconds: 3,
+        ..default_config()
+    };
+    let (env, player1, _player2, heist_id, _hub, _session_seed, _map_commitment, _p1_pos, _p2_pos) =
+        setup_active_game_with_config(session_id, config);
+    let heist = HeistContractClient::new(&env, &heist_id);
+
+    env.ledger().with_mut(|li| li.timestamp += 29);
+    heist.end_if_finished(&session_id);
+    assert_eq!(heist.get_game_view(&session_id).status, GameStatus::Active);
+
+    let turn0 = first_turn(&env, &heist_id, session_id, &player1, 0, 0, 0);
+    let pi_hash = turn_pi_hash(&env, &heist_id, &turn0, 1);
+    heist.submit_turn(&session_id, &player1, &make_test_proof_blob(&env, &pi_hash), &turn0);
+
+    let g = heist.get_game_view(&session_id);
+    assert_eq!(g.p1_time_remaining, 1 + 3);
+    assert_eq!(g.last_turn_start_ts, env.ledger().timestamp());
+}
+
+#[test]
+fn turn_landing_exactly_on_the_flag_times_out() {
+    use soroban_sdk::testutils::Ledger;
+
+    let session_id = 128u32;
+    let config = GameConfig {
+        time_per_player: 30,
+        increment_seconds: 60,
+        ..default_config()
+    };
+    let (env, player1, _player2, heist_id, _hub, _session_seed, _map_commitment, _p1_pos, _p2_pos) =
+        setup_active_game_with_config(session_id, config);
+    let heist = HeistContractClient::new(&env, &heist_id);
+
+    // The increment is only credited to accepted turns, so it cannot save a
+    // player whose clock has already reached zero.
+    env.ledger().with_mut(|li| li.timestamp += 30);
+    let turn0 = first_turn(&env, &heist_id, session_id, &player1, 0, 0, 0);
+    let pi_hash = turn_pi_hash(&env, &heist_id, &turn0, 1);
+    assert_eq!(
+        heist.try_submit_turn(&session_id, &player1, &make_test_proof_blob(&env, &pi_hash), &turn0),
+        Err(Ok(Error::TimerExpired))
+    );
+
+    heist.end_if_finished(&session_id);
+    let g = heist.get_game_view(&session_id);
+    assert_eq!(g.status, GameStatus::Ended);
+    assert_eq!(g.p1_time_remaining, 0);
+    assert_eq!(g.outcome, GameOutcome::P1Win(EndReason::Timeout)); // level scores: player1 tiebreak
+}
+
+#[test]
+fn turn_limit_adjudicates_on_score() {
+    let session_id = 129u32;
+    let config = GameConfig { max_turns: 2, ..default_config() };
+    let (env, player1, player2, heist_id, hub, _session_seed, _map_commitment, _p1_pos, _p2_pos) =
+        setup_active_game_with_config(session_id, config);
+    let heist = HeistContractClient::new(&env, &heist_id);
+
+    let turn0 = first_turn(&env, &heist_id, session_id, &player1, 1, 1, 0b1);
+    let pi_hash = turn_pi_hash(&env, &heist_id, &turn0, 1);
+    heist.submit_turn(&session_id, &player1, &make_test_proof_blob(&env, &pi_hash), &turn0);
+    assert_eq!(heist.get_game_view(&session_id).status, GameStatus::Active);
+
+    let turn1 = player2_idle_turn(&env, &heist_id, session_id, &player2);
+    let pi_hash = turn_pi_hash(&env, &heist_id, &turn1, 2);
+    heist.submit_turn(&session_id, &player2, &make_test_proof_blob(&env, &pi_hash), &turn1);
+
+    let g = heist.get_game_view(&session_id);
+    assert_eq!(g.status, GameStatus::Ended);
+    assert_eq!(g.outcome, GameOutcome::P1Win(EndReason::TurnLimit));
+    assert!(hub.ended(&session_id));
+    assert!(hub.player1_won(&session_id));
+}
+
+#[test]
+fn turn_limit_with_level_scores_and_no_exit_is_a_draw() {
+    let session_id = 130u32;
+    let config = GameConfig { max_turns: 2, ..default_config() };
+    let (env, player1, player2, heist_id, hub, _session_seed, _map_commitment, _p1_pos, _p2_pos) =
+        setup_active_game_with_config(session_id, config);
+    let heist = HeistContractClient::new(&env, &heist_id);
+
+    let turn0 = first_turn(&env, &heist_id, session_id, &player1, 0, 0, 0);
+    let pi_hash = turn_pi_hash(&env, &heist_id, &turn0, 1);
+    heist.submit_turn(&session_id, &player1, &make_test_proof_blob(&env, &pi_hash), &turn0);
+    let turn1 = player2_idle_turn(&env, &heist_id, session_id, &player2);
+    let pi_hash = turn_pi_hash(&env, &heist_id, &turn1, 2);
+    heist.submit_turn(&session_id, &player2, &make_test_proof_blob(&env, &pi_hash), &turn1);
+    let draw = GameOutcome::Draw(EndReason::TurnLimit);
+    assert!(result_not_reported(&env, &heist_id, session_id, draw.clone()));
+
+    let g = heist.get_game_view(&session_id);
+    assert_eq!(g.status, GameStatus::Ended);
+    assert_eq!(g.winner, None);
+    assert_eq!(g.outcome, draw);
+    assert!(!hub.ended(&session_id));
+}
+
+#[test]
+fn accepted_turn_publishes_typed_event() {
+    use soroban_sdk::{testutils::Events, Event};
+
+    let session_id = 131u32;
+    let (env, player1, player2, heist_id, _hub, _session_seed, _map_commitment, _p1_pos, _p2_pos) =
+        setup_active_game(session_id);
+    let heist = HeistContractClient::new(&env, &heist_id);
+
+    let turn0 = first_turn(&env, &heist_id, session_id, &player1, 1, 1, 0b1);
+    let blob = make_test_proof_blob(&env, &turn_pi_hash(&env, &heist_id, &turn0, 1));
+    heist.submit_turn(&session_id, &player1, &blob, &turn0);
+    // Read before any further call: the recorder only keeps the last invocation.
+    let events = env.events().all().filter_by_contract(&heist_id);
+
+    let accepted = TurnAccepted {
+        session_id,
+        turn_index: 0,
+        player: player1,
+        score_delta: 1,
+        loot_delta: 1,
+        loot_mask: 0b1,
+        exited_flag: false,
+        pos_commit_after: turn0.pos_commit_after,
+        state_commitment: turn0.state_commit_after,
+        proof_id: env.crypto().keccak256(&blob).into(),
+        p1_time_remaining: PLAYER_TIME_SECONDS,
+        p2_time_remaining: PLAYER_TIME_SECONDS,
+        active_player: player2,
+    };
+    assert_eq!(events.events().last(), Some(&accepted.to_xdr(&env, &heist_id)));
+}
+
+#[test]
+fn verifier_rejection_surfaces_as_proof_invalid() {
+    let session_id = 132u32;
+    let (env, player1, _player2, heist_id, _hub, _session_seed, _map_commitment, _p1_pos, _p2_pos) =
+        setup_active_game(session_id);
+    let heist = HeistContractClient::new(&env, &heist_id);
+    heist.set_verifier(&env.register(MockRejectingVerifierContract, ()));
+
+    let turn0 = first_turn(&env, &heist_id, session_id, &player1, 0, 0, 0);
+    let blob = make_test_proof_blob(&env, &turn_pi_hash(&env, &heist_id, &turn0, 1));
+    assert_eq!(
+        heist.try_submit_turn(&session_id, &player1, &blob, &turn0),
+        Err(Ok(Error::ProofInvalid))
+    );
+
+    // The rejected turn left the game untouched.
+    let g = heist.get_game_view(&session_id);
+    assert_eq!(g.turn_index, 0);
+    assert_eq!(g.active_player, player1);
+}
+
+#[test]
+fn game_keeps_verifying_under_the_key_pinned_at_begin_match() {
+    let session_id = 133u32;
+    let (env, player1, player2, heist, _hub, s1, s2) = setup_started_game(session_id);
+    let verifier_id = env.register(MockVerifierContract, ());
+    let verifier = MockVerifierContractClient::new(&env, &verifier_id);
+    heist.set_verifier(&verifier_id);
+
+    let old_vk = BytesN::from_array(&env, &[0x01u8; 32]);
+    let new_vk = BytesN::from_array(&env, &[0x02u8; 32]);
+    verifier.set_default_vk(&old_vk);
+
+    heist.reveal_seed(&session_id, &player1, &s1);
+    heist.reveal_seed(&session_id, &player2, &s2);
+    let map_commitment = BytesN::from_array(&env, &[0x11u8; 32]);
+    let p1_pos_commit = BytesN::from_array(&env, &[0x22u8; 32]);
+    let p2_pos_commit = BytesN::from_array(&env, &[0x33u8; 32]);
+    heist.begin_match(&session_id, &map_commitment, &p1_pos_commit, &p2_pos_commit);
+    assert_eq!(heist.get_game_view(&session_id).vk_id, Some(old_vk.clone()));
+
+    // A new circuit's key becomes the verifier default mid-game.
+    verifier.set_default_vk(&new_vk);
+
+    let turn0 = first_turn(&env, &heist.address, session_id, &player1, 0, 0, 0);
+    let blob = make_test_proof_blob(&env, &turn_pi_hash(&env, &heist.address, &turn0, 1));
+    heist.submit_turn(&session_id, &player1, &blob, &turn0);
+    assert_eq!(verifier.last_vk(), Some(old_vk));
+}
+
+#[test]
+#[cfg(not(feature = "multi-input-schema"))]
+fn schema_v6_is_not_selectable_without_a_circuit() {
+    let (_env, _player1, _player2, heist, _hub, _s1, _s2) = setup_started_game(138);
+    assert_eq!(heist.try_set_pi_schema(&PI_SCHEMA_V6), Err(Ok(Error::UnsupportedPiSchema)));
+}
+
+#[test]
+#[cfg(feature = "multi-input-schema")]
+fn schema_v6_checks_each_public_input_separately() {
+    let session_id = 134u32;
+    let (env, player1, player2, heist, _hub, s1, s2) = setup_started_game(session_id);
+    heist.set_pi_schema(&PI_SCHEMA_V6);
+    heist.reveal_seed(&session_id, &player1, &s1);
+    heist.reveal_seed(&session_id, &player2, &s2);
+    let map_commitment = BytesN::from_array(&env, &[0x11u8; 32]);
+    let p1_pos_commit = BytesN::from_array(&env, &[0x22u8; 32]);
+    let p2_pos_commit = BytesN::from_array(&env, &[0x33u8; 32]);
+    heist.begin_match(&session_id, &map_commitment, &p1_pos_commit, &p2_pos_commit);
+    assert_eq!(heist.get_game_view(&session_id).pi_schema, PI_SCHEMA_V6);
+
+    let turn0 = first_turn(&env, &heist.address, session_id, &player1, 1, 1, 0b1);
+    let inputs = env.as_contract(&heist.address, || {
+        compute_turn_public_inputs_v6(&env, &turn0, 1)
+    });
+    assert_eq!(inputs.len(), 3);
+
+    // A single-input blob is too short for v6.
+    let single = make_test_proof_blob(&env, &inputs.get(0).unwrap());
+    assert_eq!(
+        heist.try_submit_turn(&session_id, &player1, &single, &turn0),
+        Err(Ok(Error::ProofRequired))
+    );
+
+    // Long enough, but claims a different input count.
+    let mut miscounted = make_test_proof_blob_multi(&env, &inputs);
+    miscounted.set(3, 4);
+    assert_eq!(
+        heist.try_submit_turn(&session_id, &player1, &miscounted, &turn0),
+        Err(Ok(Error::InvalidPublicInput))
+    );
+
+    // Each input is checked, not just the first.
+    let mut tampered = inputs.clone();
+    tampered.set(2, BytesN::from_array(&env, &[0x44u8; 32]));
+    assert_eq!(
+        heist.try_submit_turn(
+            &session_id, &player1, &make_test_proof_blob_multi(&env, &tampered), &turn0,
+        ),
+        Err(Ok(Error::InvalidPublicInput))
+    );
+
+    heist.submit_turn(&session_id, &player1, &make_test_proof_blob_multi(&env, &inputs), &turn0);
+    let g = heist.get_game_view(&session_id);
+    assert_eq!(g.turn_index, 1);
+    assert_eq!(g.player1_score, 1);
+}
@@ -405,6 +405,19 @@ if (UPGRADE_HEIST_ID) {
   zkContractId = existing.zk_verifier_id ?? '';
   console.log(`Reusing zk-verifier: ${zkContractId}`);
 
+  // The new heist verifies turns through consume_proof; registering it first
+  // fails loudly on a verifier too old to have it.
+  step(`Register heist as zk-verifier consumer (${NETWORK})`);
+  stellar(
+    'contract', 'invoke',
+    ...NETWORK_ARGS,
+    '--source-account', SOURCE,
+    '--id', zkContractId,
+    '--', 'set_consumer',
+    '--consumer', heistContractId,
+    '--allowed', 'true',
+  );
+
   step(`Upload new heist WASM (${NETWORK})`);
   wasmHash = stripQuotes(stellar(
     'contract', 'upload',
@@ -439,6 +452,27 @@ if (UPGRADE_HEIST_ID) {
   zkContractId = extractContractId(zkRaw, 'zk-verifier');
   console.log(`  zk-verifier: ${zkContractId}`);
 
+  // set_vk checks key points in a call to a second verifier deployment, which
+  // turns the host's trap on a bad point into a typed error.
+  step(`Deploy zk-verifier point checker (${NETWORK})`);
+  const checkerRaw = stellarRetry([
+    'contract', 'deploy',
+    ...NETWORK_ARGS,
+    '--source-account', SOURCE,
+    '--wasm', ZK_WASM,
+    '--', '--admin', adminAddress,
+  ], 'zk-verifier point checker deploy');
+  const checkerContractId = extractContractId(checkerRaw, 'zk-verifier point checker');
+  stellar(
+    'contract', 'invoke',
+    ...NETWORK_ARGS,
+    '--source-account', SOURCE,
+    '--id', zkContractId,
+    '--', 'set_point_checker',
+    '--checker', checkerContractId,
+  );
+  console.log(`  point checker: ${checkerContractId}`);
+
   step(`Deploy heist (${NETWORK})`);
   let heistRaw = '';
   const MAX_ATTEMPTS = 3;
@@ -462,6 +496,18 @@ if (UPGRADE_HEIST_ID) {
   }
   heistContractId = extractContractId(heistRaw, 'heist');
   console.log(`  heist: ${heistContractId}`);
+
+  // Only a registered consumer can use up proofs (and have replays rejected).
+  step(`Register heist as zk-verifier consumer (${NETWORK})`);
+  stellar(
+    'contract', 'invoke',
+    ...NETWORK_ARGS,
+    '--source-account', SOURCE,
+    '--id', zkContractId,
+    '--', 'set_consumer',
+    '--consumer', heistContractId,
+    '--allowed', 'true',
+  );
 }
 
 // ---------------------------------------------------------------------------
@@ -3,7 +3,32 @@
 //! Uses Protocol 25 BN254 host functions exclusively (no ark-* crates).
 //! WASM size: ~15 KB.
 //!
-//! ## VK binary format (stored via set_vk)
+//! ## VK registry
+//! VKs are registered under their SHA-256 hash (the vk id) and kept in
+//! persistent storage, so several circuit versions can be live at once. Games
+//! pin the id they started with and verify through `verify_proof(vk_id, ..)`;
+//! `set_vk` registers a key and also makes it the default used by
+//! `verify_proof_with_stored_vk`. A key set by an earlier version, which kept
+//! it in instance storage, is moved into the registry on first use.
+//!
+//! ## Proof records
+//! `verify_proof`, `verify_proof_with_stored_vk` and `verify_batch` only check
+//! proofs. Proofs are recorded by `consume_proof`, which only a consumer the
+//! admin has registered (the heist contract) can call, so nobody can use up a
+//! player's proof by verifying it first. Each consumed proof id is recorded in
+//! temporary storage for `PROOF_TTL_LEDGERS` and then expires on its own, so
+//! the contract instance stays the same size however many turns are verified.
+//! With replay rejection on, consuming a proof id seen within that window
+//! fails with `ProofReplayed`. The admin can `prune` records early, including
+//! ids that older versions kept in instance storage.
+//!
+//! A proof id names one proof, not one statement: anyone holding a valid
+//! Groth16 proof can re-randomize it into another valid proof, with another
+//! id, for the same public inputs. Replay rejection therefore cannot stop a
+//! statement from being proven twice; consumers must bind their public inputs
+//! to a single use, as heist does with the session id and turn index.
+//!
+//! ## VK binary format (stored via register_vk / set_vk)
 //! ```
 //! [  0.. 64]  alpha_g1  : G1 (32-byte x BE ‖ 32-byte y BE)
 //! [ 64..192]  beta_g2   : G2 (x0 ‖ x1 ‖ y0 ‖ y1, each 32-byte BE)
@@ -12,6 +37,14 @@
 //! [448..452]  n_ic      : u32 BE  (= n_public + 1)
 //! [452..]     IC points : n_ic × G1
 //! ```
+//! `register_vk` / `set_vk` reject a key whose length is not exactly
+//! `452 + 64·n_ic`, whose coordinates are not canonical (< p), whose α, β, γ
+//! or δ is the point at infinity, or whose points are off the curve or (for
+//! G2) outside the r-order subgroup; all of these fail with a `VerifierError`.
+//! Only the BN254 host functions check curve and subgroup membership, and they
+//! trap on a bad point, so the points are checked in a call to the point
+//! checker: another deployment of this contract, set with `set_point_checker`,
+//! whose trap comes back here as `InvalidVkPoint`.
 //!
 //! ## Proof blob format (sent by client)
 //! ```
@@ -21,15 +54,18 @@
 //! [100..228] pi_b     : G2
 //! [228..292] pi_c     : G1
 //! ```
-//! Total: 292 bytes for 1 public input.
+//! Total: 292 bytes for 1 public input; in general exactly
+//! `4 + 32·n_pub + 256`, and any other length is rejected. The proof id is
+//! keccak256(public inputs ‖ pi_a ‖ pi_b ‖ pi_c), so it names the proof itself
+//! rather than one particular encoding of the blob.
 //! Heist contract reads pi_hash at offset [4..36] — unchanged from UltraHonk layout.
 
 #![no_std]
 
 use soroban_sdk::{
-    contract, contractimpl, contracttype,
+    contract, contracterror, contractimpl, contracttype,
     crypto::bn254::{Fr, Bn254G1Affine, Bn254G2Affine},
-    Address, Bytes, BytesN, Env, Symbol, Vec, symbol_short,
+    vec, Address, Bytes, BytesN, Env, IntoVal, Symbol, Vec, U256, symbol_short,
 };
 
 // ── Storage keys ──────────────────────────────────────────────────────────────
@@ -38,10 +74,69 @@ use soroban_sdk::{
 #[derive(Clone)]
 enum DataKey {
     Admin,
+    Vk(BytesN<32>),
+    Proof(BytesN<32>),
+    RejectReplays,
+    Consumer(Address),
+    PointChecker,
 }
 
-fn key_vk() -> Symbol { symbol_short!("vk") }
+// Id of the default VK (the one used by verify_proof_with_stored_vk).
 fn key_vk_hash() -> Symbol { symbol_short!("vk_hash") }
+// The single VK kept in instance storage before the registry existed. It is
+// moved into the registry the first time it is needed.
+fn key_legacy_vk() -> Symbol { symbol_short!("vk") }
+
+// ~180 days at 5s ledgers, refreshed whenever the key is used.
+const VK_TTL_LEDGERS: u32 = 3_110_400;
+// ~30 days at 5s ledgers, the same lifetime as a heist game.
+const PROOF_TTL_LEDGERS: u32 = 518_400;
+
+// Size of pi_a ‖ pi_b ‖ pi_c at the end of a proof blob.
+const PROOF_POINTS_LEN: u32 = 64 + 128 + 64;
+
+// ── BN254 Fp modulus p (big-endian) ───────────────────────────────────────────
+// Every point coordinate must be < p; this also leaves the two flag bits unset.
+const BN254_FP_MODULUS: [u8; 32] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29,
+    0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x97, 0x81, 0x6a, 0x91, 0x68, 0x71, 0xca, 0x8d,
+    0x3c, 0x20, 0x8c, 0x16, 0xd8, 0x7c, 0xfd, 0x47,
+];
+
+// ── BN254 Fr modulus r (big-endian) ───────────────────────────────────────────
+// Public inputs must be canonical (< r): Fr::from_bytes silently reduces, so
+// x and x + r would otherwise verify as the same input under different bytes.
+const BN254_FR_MODULUS: [u8; 32] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29,
+    0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x28, 0x33, 0xe8, 0x48, 0x79, 0xb9, 0x70, 0x91,
+    0x43, 0xe1, 0xf5, 0x93, 0xf0, 0x00, 0x00, 0x01,
+];
+
+// ── Errors ────────────────────────────────────────────────────────────────────
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum VerifierError {
+    // No VK registered under the requested id (or no default VK).
+    VkNotSet = 1,
+    MalformedVk = 2,
+    MalformedProof = 3,
+    InputCountMismatch = 4,
+    NonCanonicalInput = 5,
+    PairingFailed = 6,
+    // Replay rejection is on and this proof id was already verified.
+    ProofReplayed = 7,
+    // A VK point has a non-canonical coordinate, α, β, γ or δ is the point at
+    // infinity, a point is off the curve, or a G2 point is outside the subgroup.
+    InvalidVkPoint = 8,
+    // consume_proof was called for an address that is not a registered consumer.
+    UnknownConsumer = 9,
+    // A key was registered before set_point_checker.
+    PointCheckerNotSet = 10,
+}
 
 // ── Byte helpers ──────────────────────────────────────────────────────────────
 
@@ -65,93 +160,320 @@ fn read_g2(env: &Env, blob: &Bytes, offset: u32) -> Bn254G2Affine {
     Bn254G2Affine::from_array(env, &arr)
 }
 
-fn read_fr(env: &Env, blob: &Bytes, offset: u32) -> Fr {
-    let mut arr = [0u8; 32];
-    blob.slice(offset..offset + 32).copy_into_slice(&mut arr);
-    Fr::from_bytes(BytesN::from_array(env, &arr))
+/// Reads a public input, rejecting values ≥ r instead of reducing them.
+fn read_fr(env: &Env, blob: &Bytes, offset: u32) -> Result<Fr, VerifierError> {
+    let value = U256::from_be_bytes(env, &blob.slice(offset..offset + 32));
+    let modulus = U256::from_be_bytes(env, &Bytes::from_array(env, &BN254_FR_MODULUS));
+    if value >= modulus {
+        return Err(VerifierError::NonCanonicalInput);
+    }
+    Ok(Fr::from_u256(value))
 }
 
-// ── Groth16 verification ──────────────────────────────────────────────────────
+// ── Proof blob parsing ────────────────────────────────────────────────────────
+
+/// A proof blob split into its parts, after its length has been checked
+/// against `n_pub`.
+struct ParsedGroth16Blob {
+    /// Number of public inputs.
+    n_pub: u32,
+    /// The `n_pub` public inputs, 32 bytes each, not yet range-checked.
+    public_inputs: Bytes,
+    pi_a: Bytes,
+    pi_b: Bytes,
+    pi_c: Bytes,
+}
 
-/// Verifies a Groth16 proof using Protocol 25 BN254 host functions.
-///
-/// Returns the pi_hash field element (proof_blob[4..36]) if verification passes.
+impl ParsedGroth16Blob {
+    /// keccak256(public inputs ‖ pi_a ‖ pi_b ‖ pi_c). The `n_pub` header is
+    /// implied by the length, so every byte of a valid blob is covered.
+    fn proof_id(&self, env: &Env) -> BytesN<32> {
+        let mut preimage = self.public_inputs.clone();
+        preimage.append(&self.pi_a);
+        preimage.append(&self.pi_b);
+        preimage.append(&self.pi_c);
+        env.crypto().keccak256(&preimage).into()
+    }
+}
+
+/// Validates the packed proof blob format:
+/// [4-byte big-endian n_pub][n_pub × 32 public inputs][pi_a][pi_b][pi_c]
 ///
-/// Verification equation:
-///   e(-A, B) · e(α, β) · e(vk_x, γ) · e(C, δ) == 1
-/// where:
-///   vk_x = IC[0] + Σᵢ (public_inputs[i] · IC[i+1])
-fn verify_groth16(env: &Env, vk: &Bytes, proof_blob: &Bytes) -> Option<BytesN<32>> {
-    // ── Parse VK ──────────────────────────────────────────────────────────────
+/// `n_pub` must equal `expected_n_pub` and the blob must be exactly
+/// `4 + 32·n_pub + 256` bytes long; trailing bytes are rejected so that a
+/// proof has a single encoding.
+fn parse_groth16_blob(proof_blob: &Bytes, expected_n_pub: u32) -> Result<ParsedGroth16Blob, VerifierError> {
+    if proof_blob.len() < 4 {
+        return Err(VerifierError::MalformedProof);
+    }
+    let n_pub = read_u32_be(proof_blob, 0);
+    if n_pub != expected_n_pub {
+        return Err(VerifierError::InputCountMismatch);
+    }
+    let pi_a_offset = 4 + n_pub * 32;
+    let pi_b_offset = pi_a_offset + 64;
+    let pi_c_offset = pi_b_offset + 128;
+    if proof_blob.len() != pi_a_offset + PROOF_POINTS_LEN {
+        return Err(VerifierError::MalformedProof);
+    }
+
+    Ok(ParsedGroth16Blob {
+        n_pub,
+        public_inputs: proof_blob.slice(4..pi_a_offset),
+        pi_a: proof_blob.slice(pi_a_offset..pi_b_offset),
+        pi_b: proof_blob.slice(pi_b_offset..pi_c_offset),
+        pi_c: proof_blob.slice(pi_c_offset..pi_c_offset + 64),
+    })
+}
+
+// ── Groth16 verification ──────────────────────────────────────────────────────
+
+struct Groth16Vk {
+    alpha_g1: Bn254G1Affine,
+    beta_g2: Bn254G2Affine,
+    gamma_g2: Bn254G2Affine,
+    delta_g2: Bn254G2Affine,
+    ic: Vec<Bn254G1Affine>,
+}
+
+impl Groth16Vk {
+    /// Number of public inputs a proof under this key carries.
+    fn n_pub(&self) -> u32 {
+        self.ic.len() - 1
+    }
+}
+
+/// A proof with its public inputs already folded into vk_x.
+struct PreparedProof {
+    pi_a: Bn254G1Affine,
+    pi_b: Bn254G2Affine,
+    pi_c: Bn254G1Affine,
+    vk_x: Bn254G1Affine,
+}
+
+fn parse_vk(env: &Env, vk: &Bytes) -> Result<Groth16Vk, VerifierError> {
     if vk.len() < 452 {
-        return None;
+        return Err(VerifierError::MalformedVk);
+    }
+    let n_ic = read_u32_be(vk, 448);
+    if n_ic == 0 || vk.len() as u64 != 452 + n_ic as u64 * 64 {
+        return Err(VerifierError::MalformedVk);
+    }
+    let mut ic: Vec<Bn254G1Affine> = Vec::new(env);
+    for i in 0..n_ic {
+        ic.push_back(read_g1(env, vk, 452 + i * 64));
+    }
+
+    Ok(Groth16Vk {
+        alpha_g1: read_g1(env, vk, 0),
+        beta_g2:  read_g2(env, vk, 64),
+        gamma_g2: read_g2(env, vk, 192),
+        delta_g2: read_g2(env, vk, 320),
+        ic,
+    })
+}
+
+/// Checks that each 32-byte coordinate of the `len`-byte point at `offset` is
+/// below p and, unless `allow_infinity`, that the point is not all zeros.
+/// Curve and subgroup membership are checked by `check_vk_points`.
+fn check_point_encoding(vk: &Bytes, offset: u32, len: u32, allow_infinity: bool) -> Result<(), VerifierError> {
+    let mut is_infinity = true;
+    for coord_offset in (offset..offset + len).step_by(32) {
+        let mut coord = [0u8; 32];
+        vk.slice(coord_offset..coord_offset + 32).copy_into_slice(&mut coord);
+        if coord >= BN254_FP_MODULUS {
+            return Err(VerifierError::InvalidVkPoint);
+        }
+        is_infinity &= coord == [0u8; 32];
+    }
+    if is_infinity && !allow_infinity {
+        return Err(VerifierError::InvalidVkPoint);
     }
-    let alpha_g1 = read_g1(env, vk, 0);
-    let beta_g2  = read_g2(env, vk, 64);
-    let gamma_g2 = read_g2(env, vk, 192);
-    let delta_g2 = read_g2(env, vk, 320);
-    let n_ic     = read_u32_be(vk, 448) as usize;
+    Ok(())
+}
 
-    let vk_min_len = 452 + n_ic as u32 * 64;
-    if vk.len() < vk_min_len {
-        return None;
+/// Structural checks run once when a key is registered, so a malformed key is
+/// refused up front instead of failing every proof verified under it.
+///
+/// Length and encoding problems come back as `MalformedVk` / `InvalidVkPoint`.
+/// Curve and subgroup membership are then checked by the point checker, and a
+/// key it traps on is refused with `InvalidVkPoint` as well.
+fn validate_vk(env: &Env, vk: &Bytes) -> Result<(), VerifierError> {
+    let parsed_vk = parse_vk(env, vk)?;
+    check_point_encoding(vk, 0, 64, false)?;
+    check_point_encoding(vk, 64, 128, false)?;
+    check_point_encoding(vk, 192, 128, false)?;
+    check_point_encoding(vk, 320, 128, false)?;
+    for i in 0..parsed_vk.ic.len() {
+        check_point_encoding(vk, 452 + i * 64, 64, true)?;
     }
 
-    // ── Parse proof blob ──────────────────────────────────────────────────────
-    // Expected: [4 n_pub][32 pi_hash][64 pi_a][128 pi_b][64 pi_c]
-    let n_pub = read_u32_be(proof_blob, 0) as usize;
-    if n_pub + 1 != n_ic {
-        return None; // public input count mismatch
+    let checker: Address = env
+        .storage()
+        .instance()
+        .get(&DataKey::PointChecker)
+        .ok_or(VerifierError::PointCheckerNotSet)?;
+    let checked = env.try_invoke_contract::<(), soroban_sdk::Error>(
+        &checker,
+        &Symbol::new(env, "check_vk_points"),
+        vec![env, vk.into_val(env)],
+    );
+    match checked {
+        Ok(Ok(())) => Ok(()),
+        _ => Err(VerifierError::InvalidVkPoint),
     }
-    let pub_offset: u32 = 4;
-    let pi_a_offset = pub_offset + n_pub as u32 * 32;
-    let pi_b_offset = pi_a_offset + 64;
-    let pi_c_offset = pi_b_offset + 128;
+}
 
-    if proof_blob.len() < pi_c_offset + 64 {
-        return None;
+/// Runs every point of `vk` through the BN254 host functions, which trap on a
+/// point off the curve or, for G2, outside the r-order subgroup.
+fn check_points_on_host(env: &Env, vk: &Groth16Vk) {
+    let bn = env.crypto().bn254();
+    let mut g1_sum = vk.alpha_g1.clone();
+    for ic_i in vk.ic.iter() {
+        g1_sum = bn.g1_add(&g1_sum, &ic_i);
     }
 
-    let pi_a = read_g1(env, proof_blob, pi_a_offset);
-    let pi_b = read_g2(env, proof_blob, pi_b_offset);
-    let pi_c = read_g1(env, proof_blob, pi_c_offset);
+    // Only the host's input checks matter here, not the pairing result: G2 has
+    // a cofactor, so β, γ and δ go through a pairing for the subgroup check.
+    let mut g1s: Vec<Bn254G1Affine> = Vec::new(env);
+    let mut g2s: Vec<Bn254G2Affine> = Vec::new(env);
+    for g2 in [vk.beta_g2.clone(), vk.gamma_g2.clone(), vk.delta_g2.clone()] {
+        g1s.push_back(vk.alpha_g1.clone());
+        g2s.push_back(g2);
+    }
+    bn.pairing_check(g1s, g2s);
+}
 
-    // ── Compute vk_x = IC[0] + pi_hash · IC[1] ───────────────────────────────
+fn prepare_proof(env: &Env, vk: &Groth16Vk, proof: &ParsedGroth16Blob) -> Result<PreparedProof, VerifierError> {
+    // ── Compute vk_x = IC[0] + Σᵢ public_input[i] · IC[i+1] ──────────────────
     let bn = env.crypto().bn254();
 
-    let ic0 = read_g1(env, vk, 452);
+    let ic0 = vk.ic.get_unchecked(0);
     let mut vk_x = ic0;
 
-    for i in 0..n_pub {
-        let ic_i   = read_g1(env, vk, 452 + (i as u32 + 1) * 64);
-        let scalar = read_fr(env, proof_blob, pub_offset + i as u32 * 32);
+    for i in 0..proof.n_pub {
+        let ic_i   = vk.ic.get_unchecked(i + 1);
+        let scalar = read_fr(env, &proof.public_inputs, i * 32)?;
         let term   = bn.g1_mul(&ic_i, &scalar);
         vk_x       = bn.g1_add(&vk_x, &term);
     }
 
+    Ok(PreparedProof {
+        pi_a: read_g1(env, &proof.pi_a, 0),
+        pi_b: read_g2(env, &proof.pi_b, 0),
+        pi_c: read_g1(env, &proof.pi_c, 0),
+        vk_x,
+    })
+}
+
+/// Verifies a Groth16 proof using Protocol 25 BN254 host functions.
+///
+/// Every public input must be a canonical Fr element (< r), and the blob must
+/// be exactly as long as its `n_pub` implies.
+///
+/// Verification equation:
+///   e(-A, B) · e(α, β) · e(vk_x, γ) · e(C, δ) == 1
+/// where:
+///   vk_x = IC[0] + Σᵢ (public_inputs[i] · IC[i+1])
+fn verify_groth16(env: &Env, vk: &Groth16Vk, proof_blob: &Bytes) -> Result<(), VerifierError> {
+    let parsed_blob = parse_groth16_blob(proof_blob, vk.n_pub())?;
+    let proof = prepare_proof(env, vk, &parsed_blob)?;
+
     // ── Pairing check: e(-A,B)·e(α,β)·e(vk_x,γ)·e(C,δ) == 1 ─────────────────
-    let neg_pi_a = -pi_a;
+    let neg_pi_a = -proof.pi_a;
 
     let mut g1s: Vec<Bn254G1Affine> = Vec::new(env);
     g1s.push_back(neg_pi_a);
-    g1s.push_back(alpha_g1);
-    g1s.push_back(vk_x);
-    g1s.push_back(pi_c);
+    g1s.push_back(vk.alpha_g1.clone());
+    g1s.push_back(proof.vk_x);
+    g1s.push_back(proof.pi_c);
 
     let mut g2s: Vec<Bn254G2Affine> = Vec::new(env);
-    g2s.push_back(pi_b);
-    g2s.push_back(beta_g2);
-    g2s.push_back(gamma_g2);
-    g2s.push_back(delta_g2);
+    g2s.push_back(proof.pi_b);
+    g2s.push_back(vk.beta_g2.clone());
+    g2s.push_back(vk.gamma_g2.clone());
+    g2s.push_back(vk.delta_g2.clone());
 
-    if !bn.pairing_check(g1s, g2s) {
-        return None;
+    if !env.crypto().bn254().pairing_check(g1s, g2s) {
+        return Err(VerifierError::PairingFailed);
     }
+    Ok(())
+}
+
+/// Verifies N proofs under one VK with a single multi-pairing.
+///
+/// Each proof's equation is weighted by a random rᵢ and the products merged:
+///   Πᵢ e(-rᵢ·Aᵢ, Bᵢ) · e((Σ rᵢ)·α, β) · e(Σ rᵢ·vk_xᵢ, γ) · e(Σ rᵢ·Cᵢ, δ) == 1
+/// which costs N + 3 pairings instead of 4N. rᵢ = keccak256(seed ‖ i) mod r,
+/// with seed = keccak256(proof_id₀ ‖ … ‖ proof_idₙ₋₁), so the weights are fixed
+/// only once every blob is; a forged proof passes with negligible probability.
+///
+/// `proof_ids[i]` must be the proof id of `proof_blobs[i]`.
+fn verify_groth16_batch(
+    env: &Env,
+    vk: &Groth16Vk,
+    proof_blobs: &Vec<Bytes>,
+    proof_ids: &Vec<BytesN<32>>,
+) -> Result<(), VerifierError> {
+    let bn = env.crypto().bn254();
+    let modulus = U256::from_be_bytes(env, &Bytes::from_array(env, &BN254_FR_MODULUS));
+
+    let mut seed_preimage = Bytes::new(env);
+    for proof_id in proof_ids.iter() {
+        seed_preimage.append(&proof_id.into());
+    }
+    let seed: BytesN<32> = env.crypto().keccak256(&seed_preimage).into();
+
+    let mut g1s: Vec<Bn254G1Affine> = Vec::new(env);
+    let mut g2s: Vec<Bn254G2Affine> = Vec::new(env);
+    let mut r_sum = U256::from_u32(env, 0);
+    let mut vk_x_sum: Option<Bn254G1Affine> = None;
+    let mut pi_c_sum: Option<Bn254G1Affine> = None;
+
+    for (i, proof_blob) in proof_blobs.iter().enumerate() {
+        let parsed_blob = parse_groth16_blob(&proof_blob, vk.n_pub())?;
+        let proof = prepare_proof(env, vk, &parsed_blob)?;
+
+        let mut r_preimage = Bytes::from(seed.clone());
+        r_preimage.extend_from_array(&(i as u32).to_be_bytes());
+        let r_i = Fr::from_bytes(env.crypto().keccak256(&r_preimage).into());
+
+        g1s.push_back(-bn.g1_mul(&proof.pi_a, &r_i));
+        g2s.push_back(proof.pi_b);
+
+        let vk_x_term = bn.g1_mul(&proof.vk_x, &r_i);
+        vk_x_sum = Some(match vk_x_sum {
+            Some(sum) => bn.g1_add(&sum, &vk_x_term),
+            None => vk_x_term,
+        });
+        let pi_c_term = bn.g1_mul(&proof.pi_c, &r_i);
+        pi_c_sum = Some(match pi_c_sum {
+            Some(sum) => bn.g1_add(&sum, &pi_c_term),
+            None => pi_c_term,
+        });
+
+        // Both terms are < r, so the sum cannot overflow 256 bits.
+        r_sum = r_sum.add(&r_i.to_u256());
+        if r_sum >= modulus {
+            r_sum = r_sum.sub(&modulus);
+        }
+    }
+
+    let (Some(vk_x_sum), Some(pi_c_sum)) = (vk_x_sum, pi_c_sum) else {
+        return Ok(()); // empty batch
+    };
+
+    g1s.push_back(bn.g1_mul(&vk.alpha_g1, &Fr::from_u256(r_sum)));
+    g2s.push_back(vk.beta_g2.clone());
+    g1s.push_back(vk_x_sum);
+    g2s.push_back(vk.gamma_g2.clone());
+    g1s.push_back(pi_c_sum);
+    g2s.push_back(vk.delta_g2.clone());
 
-    // Return the public input (pi_hash) as BytesN<32>
-    let mut pi_arr = [0u8; 32];
-    proof_blob.slice(pub_offset..pub_offset + 32).copy_into_slice(&mut pi_arr);
-    Some(BytesN::from_array(env, &pi_arr))
+    if !bn.pairing_check(g1s, g2s) {
+        return Err(VerifierError::PairingFailed);
+    }
+    Ok(())
 }
 
 // ── Contract ──────────────────────────────────────────────────────────────────
@@ -180,61 +502,270 @@ impl ZkVerifierContract {
 
     // ── VK management ──────────────────────────────────────────────────────────
 
-    /// Store the Groth16 verification key (binary format) and return its SHA-256 hash.
-    /// Admin-only.
-    pub fn set_vk(env: Env, vk: Bytes) -> BytesN<32> {
-        let admin: Address = env
-            .storage()
-            .instance()
-            .get(&DataKey::Admin)
-            .expect("admin not set");
-        admin.require_auth();
+    /// Registers a Groth16 verification key (binary format) and returns its
+    /// SHA-256 hash, which is its id. Admin-only. Re-registering a key only
+    /// refreshes its TTL. Malformed keys are rejected (see `validate_vk`).
+    pub fn register_vk(env: Env, vk: Bytes) -> Result<BytesN<32>, VerifierError> {
+        Self::require_admin(&env);
+        Self::store_vk(&env, &vk)
+    }
 
-        env.storage().instance().set(&key_vk(), &vk);
+    /// Removes a key from the registry; proofs under it no longer verify.
+    /// Admin-only. Retiring the default key also clears the default.
+    pub fn retire_vk(env: Env, vk_id: BytesN<32>) {
+        Self::require_admin(&env);
+        Self::migrate_legacy_vk(&env);
+        env.storage().persistent().remove(&DataKey::Vk(vk_id.clone()));
+        if env.storage().instance().get::<_, BytesN<32>>(&key_vk_hash()) == Some(vk_id) {
+            env.storage().instance().remove(&key_vk_hash());
+        }
+    }
+
+    pub fn is_vk_registered(env: Env, vk_id: BytesN<32>) -> bool {
+        env.storage().persistent().has(&DataKey::Vk(vk_id.clone()))
+            || env
+                .storage()
+                .instance()
+                .get::<_, Bytes>(&key_legacy_vk())
+                .is_some_and(|vk| BytesN::from(env.crypto().sha256(&vk)) == vk_id)
+    }
 
-        let hash: BytesN<32> = env.crypto().sha256(&vk).into();
+    /// Registers the Groth16 verification key (binary format), makes it the
+    /// default and returns its SHA-256 hash. Admin-only. Malformed keys are
+    /// rejected and leave the current default in place.
+    pub fn set_vk(env: Env, vk: Bytes) -> Result<BytesN<32>, VerifierError> {
+        Self::require_admin(&env);
+        // Keep the outgoing legacy key usable by id, like any other default.
+        Self::migrate_legacy_vk(&env);
+        let hash = Self::store_vk(&env, &vk)?;
         env.storage().instance().set(&key_vk_hash(), &hash);
-        hash
+        Ok(hash)
     }
 
+    /// Id of the default VK, or `None` if none is set.
     pub fn get_vk_hash(env: Env) -> Option<BytesN<32>> {
         env.storage().instance().get(&key_vk_hash())
     }
 
+    /// Sets the contract that checks key points for `register_vk` / `set_vk`:
+    /// another deployment of this contract. Admin-only. It has to be a
+    /// separate contract because a contract cannot call itself.
+    pub fn set_point_checker(env: Env, checker: Address) {
+        Self::require_admin(&env);
+        env.storage().instance().set(&DataKey::PointChecker, &checker);
+    }
+
+    /// Traps unless every point of `vk` is on its curve and, for G2, in the
+    /// r-order subgroup. Stateless; called by another deployment's
+    /// `validate_vk`, which turns the trap into `InvalidVkPoint`.
+    pub fn check_vk_points(env: Env, vk: Bytes) -> Result<(), VerifierError> {
+        check_points_on_host(&env, &parse_vk(&env, &vk)?);
+        Ok(())
+    }
+
     // ── Proof verification ─────────────────────────────────────────────────────
 
-    /// Verifies a Groth16 proof and returns keccak256(proof_blob) on success.
+    /// Verifies a Groth16 proof against the registered key `vk_id` and returns
+    /// its proof id (keccak256 of the inputs and points) on success. Nothing is
+    /// recorded; see `consume_proof`.
+    pub fn verify_proof(env: Env, vk_id: BytesN<32>, proof_blob: Bytes) -> Result<BytesN<32>, VerifierError> {
+        let vk = parse_vk(&env, &Self::load_vk(&env, vk_id)?)?;
+        let proof_id = Self::proof_id(&env, &vk, &proof_blob)?;
+        verify_groth16(&env, &vk, &proof_blob)?;
+        Ok(proof_id)
+    }
+
+    /// Verifies every proof in `proof_blobs` against the registered key `vk_id`
+    /// with one multi-pairing, and returns their proof ids in order. The batch
+    /// passes or fails as a whole. Nothing is recorded.
+    pub fn verify_batch(env: Env, vk_id: BytesN<32>, proof_blobs: Vec<Bytes>) -> Result<Vec<BytesN<32>>, VerifierError> {
+        let vk = parse_vk(&env, &Self::load_vk(&env, vk_id)?)?;
+
+        let mut proof_ids: Vec<BytesN<32>> = Vec::new(&env);
+        for proof_blob in proof_blobs.iter() {
+            proof_ids.push_back(Self::proof_id(&env, &vk, &proof_blob)?);
+        }
+
+        verify_groth16_batch(&env, &vk, &proof_blobs, &proof_ids)?;
+        Ok(proof_ids)
+    }
+
+    /// Verifies a Groth16 proof against the default VK and returns its proof
+    /// id on success. Nothing is recorded.
     ///
     /// The heist contract extracts pi_hash from proof_blob[4..36] and verifies
     /// it matches the expected public data independently.
-    pub fn verify_proof_with_stored_vk(env: Env, proof_blob: Bytes) -> BytesN<32> {
-        let vk: Bytes = env
-            .storage()
-            .instance()
-            .get(&key_vk())
-            .expect("vk not set");
-
-        verify_groth16(&env, &vk, &proof_blob)
-            .expect("groth16 verification failed");
+    pub fn verify_proof_with_stored_vk(env: Env, proof_blob: Bytes) -> Result<BytesN<32>, VerifierError> {
+        let vk_id = Self::default_vk_id(&env)?;
+        Self::verify_proof(env, vk_id, proof_blob)
+    }
 
-        let proof_id: BytesN<32> = env.crypto().keccak256(&proof_blob).into();
-        env.storage().instance().set(&proof_id, &true);
-        proof_id
+    /// Verifies a proof on behalf of `consumer` and records its proof id. With
+    /// replay rejection on, an id already recorded fails with `ProofReplayed`.
+    /// `vk_id` selects a registered key; `None` uses the default.
+    ///
+    /// `consumer` must authorize the call and be registered with
+    /// `set_consumer`, so only it can use up its players' proofs.
+    pub fn consume_proof(
+        env: Env,
+        consumer: Address,
+        vk_id: Option<BytesN<32>>,
+        proof_blob: Bytes,
+    ) -> Result<BytesN<32>, VerifierError> {
+        consumer.require_auth();
+        if !Self::is_consumer(env.clone(), consumer) {
+            return Err(VerifierError::UnknownConsumer);
+        }
+        let vk_id = match vk_id {
+            Some(vk_id) => vk_id,
+            None => Self::default_vk_id(&env)?,
+        };
+        let vk = parse_vk(&env, &Self::load_vk(&env, vk_id)?)?;
+        let proof_id = Self::proof_id(&env, &vk, &proof_blob)?;
+        Self::check_replay(&env, &proof_id)?;
+
+        verify_groth16(&env, &vk, &proof_blob)?;
+
+        Self::record_proof(&env, &proof_id);
+        Ok(proof_id)
     }
 
+    /// Whether `proof_id` was consumed and its record has not expired or been
+    /// pruned.
     pub fn is_verified(env: Env, proof_id: BytesN<32>) -> bool {
-        env.storage().instance().get(&proof_id).unwrap_or(false)
+        env.storage().temporary().has(&DataKey::Proof(proof_id.clone()))
+            || env.storage().instance().get(&proof_id).unwrap_or(false)
+    }
+
+    // ── Replay protection ──────────────────────────────────────────────────────
+
+    /// Allows or disallows `consumer` to call `consume_proof`. Admin-only.
+    pub fn set_consumer(env: Env, consumer: Address, allowed: bool) {
+        Self::require_admin(&env);
+        let key = DataKey::Consumer(consumer);
+        if allowed {
+            env.storage().instance().set(&key, &true);
+        } else {
+            env.storage().instance().remove(&key);
+        }
+    }
+
+    pub fn is_consumer(env: Env, consumer: Address) -> bool {
+        env.storage().instance().has(&DataKey::Consumer(consumer))
+    }
+
+    /// Turns rejection of already-verified proof ids on or off. Admin-only.
+    pub fn set_reject_replays(env: Env, enabled: bool) {
+        Self::require_admin(&env);
+        env.storage().instance().set(&DataKey::RejectReplays, &enabled);
+    }
+
+    pub fn rejects_replays(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&DataKey::RejectReplays)
+            .unwrap_or(false)
+    }
+
+    /// Drops the records of `proof_ids` before they expire, including records
+    /// kept in instance storage by earlier versions of this contract.
+    /// Admin-only. A pruned id can be verified (and replayed) again.
+    pub fn prune(env: Env, proof_ids: Vec<BytesN<32>>) {
+        Self::require_admin(&env);
+        for proof_id in proof_ids.iter() {
+            env.storage().instance().remove(&proof_id);
+            env.storage().temporary().remove(&DataKey::Proof(proof_id));
+        }
     }
 
     // ── Upgrade ────────────────────────────────────────────────────────────────
 
     pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) {
+        Self::require_admin(&env);
+        env.deployer().update_current_contract_wasm(new_wasm_hash);
+    }
+
+    // ── Internal ───────────────────────────────────────────────────────────────
+
+    fn require_admin(env: &Env) {
         let admin: Address = env
             .storage()
             .instance()
             .get(&DataKey::Admin)
             .expect("admin not set");
         admin.require_auth();
-        env.deployer().update_current_contract_wasm(new_wasm_hash);
+    }
+
+    /// Checks the blob's shape against `vk` and returns its proof id.
+    fn proof_id(env: &Env, vk: &Groth16Vk, proof_blob: &Bytes) -> Result<BytesN<32>, VerifierError> {
+        Ok(parse_groth16_blob(proof_blob, vk.n_pub())?.proof_id(env))
+    }
+
+    fn check_replay(env: &Env, proof_id: &BytesN<32>) -> Result<(), VerifierError> {
+        if Self::rejects_replays(env.clone()) && Self::is_verified(env.clone(), proof_id.clone()) {
+            return Err(VerifierError::ProofReplayed);
+        }
+        Ok(())
+    }
+
+    fn record_proof(env: &Env, proof_id: &BytesN<32>) {
+        let key = DataKey::Proof(proof_id.clone());
+        env.storage().temporary().set(&key, &true);
+        env.storage()
+            .temporary()
+            .extend_ttl(&key, PROOF_TTL_LEDGERS, PROOF_TTL_LEDGERS);
+    }
+
+    fn default_vk_id(env: &Env) -> Result<BytesN<32>, VerifierError> {
+        env.storage()
+            .instance()
+            .get(&key_vk_hash())
+            .or_else(|| Self::migrate_legacy_vk(env))
+            .ok_or(VerifierError::VkNotSet)
+    }
+
+    fn load_vk(env: &Env, vk_id: BytesN<32>) -> Result<Bytes, VerifierError> {
+        let key = DataKey::Vk(vk_id);
+        if !env.storage().persistent().has(&key) {
+            Self::migrate_legacy_vk(env);
+        }
+        let vk: Bytes = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(VerifierError::VkNotSet)?;
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, VK_TTL_LEDGERS, VK_TTL_LEDGERS);
+        Ok(vk)
+    }
+
+    fn store_vk(env: &Env, vk: &Bytes) -> Result<BytesN<32>, VerifierError> {
+        validate_vk(env, vk)?;
+        Ok(Self::put_vk(env, vk))
+    }
+
+    /// Moves the legacy instance-storage VK, if any, into the registry and
+    /// makes it the default (as it already was), returning its id. The key
+    /// was accepted before `validate_vk` existed and is kept as it is.
+    fn migrate_legacy_vk(env: &Env) -> Option<BytesN<32>> {
+        let vk: Bytes = env.storage().instance().get(&key_legacy_vk())?;
+        env.storage().instance().remove(&key_legacy_vk());
+        let hash = Self::put_vk(env, &vk);
+        env.storage().instance().set(&key_vk_hash(), &hash);
+        Some(hash)
+    }
+
+    fn put_vk(env: &Env, vk: &Bytes) -> BytesN<32> {
+        let hash: BytesN<32> = env.crypto().sha256(vk).into();
+        let key = DataKey::Vk(hash.clone());
+        env.storage().persistent().set(&key, vk);
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, VK_TTL_LEDGERS, VK_TTL_LEDGERS);
+        hash
     }
 }
+
+#[cfg(test)]
+mod test;
@@ -1,99 +1,688 @@
 #![cfg(test)]
 
-use soroban_sdk::{testutils::Address as _, Address, Bytes, BytesN, Env};
+use soroban_sdk::{
+    crypto::bn254::{Bn254G1Affine, Fr},
+    testutils::{
+        storage::{Instance as _, Temporary as _},
+        Address as _, Ledger as _,
+    },
+    vec, Address, Bytes, BytesN, Env, Vec, U256,
+};
 
-use crate::{ZkVerifierContract, ZkVerifierContractClient};
+use crate::{
+    parse_vk, verify_groth16, VerifierError, ZkVerifierContract, ZkVerifierContractClient,
+    BN254_FP_MODULUS, BN254_FR_MODULUS, PROOF_TTL_LEDGERS,
+};
 
-/// Build a valid proof_blob: [count=1 (4 bytes)] [pi_hash (32 bytes)] [dummy proof byte]
-fn build_valid_proof(env: &Env, pi_hash: &BytesN<32>) -> Bytes {
+// BN254 generators. G2 coordinates are Fp2 elements encoded c1 ‖ c0, as the
+// host functions (and snarkjs' vk.bin export) expect.
+const G1_GEN: [u8; 64] = {
+    let mut g = [0u8; 64];
+    g[31] = 1;
+    g[63] = 2;
+    g
+};
+const G2_GEN: [u8; 128] = [
+    0x19, 0x8e, 0x93, 0x93, 0x92, 0x0d, 0x48, 0x3a, 0x72, 0x60, 0xbf, 0xb7, 0x31, 0xfb, 0x5d, 0x25,
+    0xf1, 0xaa, 0x49, 0x33, 0x35, 0xa9, 0xe7, 0x12, 0x97, 0xe4, 0x85, 0xb7, 0xae, 0xf3, 0x12, 0xc2,
+    0x18, 0x00, 0xde, 0xef, 0x12, 0x1f, 0x1e, 0x76, 0x42, 0x6a, 0x00, 0x66, 0x5e, 0x5c, 0x44, 0x79,
+    0x67, 0x43, 0x22, 0xd4, 0xf7, 0x5e, 0xda, 0xdd, 0x46, 0xde, 0xbd, 0x5c, 0xd9, 0x92, 0xf6, 0xed,
+    0x09, 0x06, 0x89, 0xd0, 0x58, 0x5f, 0xf0, 0x75, 0xec, 0x9e, 0x99, 0xad, 0x69, 0x0c, 0x33, 0x95,
+    0xbc, 0x4b, 0x31, 0x33, 0x70, 0xb3, 0x8e, 0xf3, 0x55, 0xac, 0xda, 0xdc, 0xd1, 0x22, 0x97, 0x5b,
+    0x12, 0xc8, 0x5e, 0xa5, 0xdb, 0x8c, 0x6d, 0xeb, 0x4a, 0xab, 0x71, 0x80, 0x8d, 0xcb, 0x40, 0x8f,
+    0xe3, 0xd1, 0xe7, 0x69, 0x0c, 0x43, 0xd3, 0x7b, 0x4c, 0xe6, 0xcc, 0x01, 0x66, 0xfa, 0x7d, 0xaa,
+];
+
+// The point on the G2 twist with x = 1. Its order is not r, so it is on the
+// curve but outside the subgroup the pairing is defined on.
+const G2_OFF_SUBGROUP: [u8; 128] = [
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+    0x0d, 0x12, 0x71, 0x95, 0x3e, 0xd9, 0xea, 0x08, 0x36, 0x84, 0x6e, 0x70, 0xa1, 0x93, 0x41, 0x87,
+    0x99, 0x8c, 0x7f, 0x79, 0x0c, 0xb4, 0xd7, 0x51, 0x1b, 0x7f, 0x8d, 0xa8, 0x2d, 0xe0, 0x48, 0xa4,
+    0x28, 0x69, 0x11, 0x1d, 0x53, 0x81, 0xf0, 0x72, 0xf8, 0xe2, 0x72, 0x8f, 0xdb, 0x82, 0x5a, 0x51,
+    0xaa, 0xdd, 0x70, 0xe5, 0x2c, 0x98, 0x30, 0xe9, 0xab, 0x4b, 0x87, 0x1c, 0x05, 0x31, 0xf1, 0xbb,
+];
+
+// Fixture scalars: alpha = ALPHA·G1, IC[0] = IC0·G1, IC[1] = G1, C = C_SCALAR·G1.
+const ALPHA: u32 = 5;
+const IC0: u32 = 7;
+const C_SCALAR: u32 = 11;
+
+fn g1_times(env: &Env, k: u32) -> Bn254G1Affine {
+    let g = Bn254G1Affine::from_array(env, &G1_GEN);
+    env.crypto().bn254().g1_mul(&g, &Fr::from_u256(U256::from_u32(env, k)))
+}
+
+/// A one-input VK whose G2 points are all the generator, so the Groth16
+/// equation collapses to a scalar identity on the G1 side.
+fn build_vk(env: &Env) -> Bytes {
+    build_vk_with_alpha(env, ALPHA)
+}
+
+/// Same shape as `build_vk`; a different `alpha` gives a distinct key.
+fn build_vk_with_alpha(env: &Env, alpha: u32) -> Bytes {
+    let mut vk = Bytes::new(env);
+    vk.append(&g1_times(env, alpha).to_bytes().into());
+    vk.extend_from_array(&G2_GEN); // beta
+    vk.extend_from_array(&G2_GEN); // gamma
+    vk.extend_from_array(&G2_GEN); // delta
+    vk.extend_from_array(&2u32.to_be_bytes()); // n_ic
+    vk.append(&g1_times(env, IC0).to_bytes().into());
+    vk.extend_from_array(&G1_GEN);
+    vk
+}
+
+/// Builds a proof that satisfies `build_vk` for `public_input`:
+/// with every G2 point equal, the check holds iff A = alpha + vk_x + C.
+fn build_valid_proof(env: &Env, public_input: &BytesN<32>) -> Bytes {
+    build_valid_proof_with_alpha(env, ALPHA, public_input)
+}
+
+fn build_valid_proof_with_alpha(env: &Env, alpha: u32, public_input: &BytesN<32>) -> Bytes {
+    let bn = env.crypto().bn254();
+    let vk_x = bn.g1_add(
+        &g1_times(env, IC0),
+        &bn.g1_mul(
+            &Bn254G1Affine::from_array(env, &G1_GEN),
+            &Fr::from_bytes(public_input.clone()),
+        ),
+    );
+    let pi_c = g1_times(env, C_SCALAR);
+    let pi_a = bn.g1_add(&bn.g1_add(&g1_times(env, alpha), &vk_x), &pi_c);
+    proof_blob(env, public_input, &pi_a, &pi_c)
+}
+
+fn proof_blob(
+    env: &Env,
+    public_input: &BytesN<32>,
+    pi_a: &Bn254G1Affine,
+    pi_c: &Bn254G1Affine,
+) -> Bytes {
     let mut proof = Bytes::new(env);
     // count = 1 as big-endian u32
-    proof.push_back(0x00);
-    proof.push_back(0x00);
-    proof.push_back(0x00);
-    proof.push_back(0x01);
+    proof.extend_from_array(&1u32.to_be_bytes());
     // 32-byte pi_hash (the single public input)
-    let mut i = 0u32;
-    while i < 32 {
-        proof.push_back(pi_hash.get(i).unwrap());
-        i += 1;
-    }
-    // At least one dummy proof byte so it's realistic
-    proof.push_back(0x07);
+    proof.append(&public_input.clone().into());
+    proof.append(&pi_a.to_bytes().into());
+    proof.extend_from_array(&G2_GEN);
+    proof.append(&pi_c.to_bytes().into());
     proof
 }
 
+/// A public input reduced modulo the Fr prime, as the circuit would output it.
+fn turn_public_input(env: &Env) -> BytesN<32> {
+    nth_public_input(env, 0)
+}
+
+fn nth_public_input(env: &Env, n: u32) -> BytesN<32> {
+    let mut preimage = Bytes::from_array(env, b"turn-public");
+    if n > 0 {
+        preimage.extend_from_array(&n.to_be_bytes());
+    }
+    let digest: BytesN<32> = env.crypto().keccak256(&preimage).into();
+    Fr::from_bytes(digest).to_bytes()
+}
+
+fn valid_proofs(env: &Env, count: u32) -> Vec<Bytes> {
+    let mut proofs = Vec::new(env);
+    for n in 0..count {
+        proofs.push_back(build_valid_proof(env, &nth_public_input(env, n)));
+    }
+    proofs
+}
+
+/// keccak256 of everything after the `n_pub` header.
+fn expected_proof_id(env: &Env, proof: &Bytes) -> BytesN<32> {
+    env.crypto().keccak256(&proof.slice(4..proof.len())).into()
+}
+
+/// `value + r`: the same field element under a different byte encoding.
+fn plus_modulus(env: &Env, value: &BytesN<32>) -> BytesN<32> {
+    let r = U256::from_be_bytes(env, &Bytes::from_array(env, &BN254_FR_MODULUS));
+    let shifted = U256::from_be_bytes(env, &value.clone().into()).add(&r);
+    shifted.to_be_bytes().try_into().unwrap()
+}
+
+fn setup(env: &Env) -> ZkVerifierContractClient<'_> {
+    let client = setup_without_point_checker(env);
+    let checker = env.register(ZkVerifierContract, (Address::generate(env),));
+    client.set_point_checker(&checker);
+    client
+}
+
+fn setup_without_point_checker(env: &Env) -> ZkVerifierContractClient<'_> {
+    env.mock_all_auths();
+    let admin = Address::generate(env);
+    let contract_id = env.register(ZkVerifierContract, (admin,));
+    ZkVerifierContractClient::new(env, &contract_id)
+}
+
+fn register_consumer(env: &Env, client: &ZkVerifierContractClient<'_>) -> Address {
+    let consumer = Address::generate(env);
+    client.set_consumer(&consumer, &true);
+    consumer
+}
+
 #[test]
 fn set_vk_and_verify_roundtrip() {
     let env = Env::default();
-    env.mock_all_auths();
-
-    let admin = Address::generate(&env);
-    let contract_id = env.register(ZkVerifierContract, (admin.clone(),));
-    let client = ZkVerifierContractClient::new(&env, &contract_id);
+    let client = setup(&env);
 
-    let vk = Bytes::from_array(&env, b"vk-test");
+    let vk = build_vk(&env);
     let vk_hash = client.set_vk(&vk);
 
-    let pi_hash: BytesN<32> = env.crypto().keccak256(&Bytes::from_array(&env, b"turn-public")).into();
+    let pi_hash = turn_public_input(&env);
     let proof = build_valid_proof(&env, &pi_hash);
     let proof_id = client.verify_proof_with_stored_vk(&proof);
 
-    assert!(client.is_verified(&proof_id));
+    assert_eq!(proof_id, expected_proof_id(&env, &proof));
+    // Checking a proof doesn't use it up.
+    assert!(!client.is_verified(&proof_id));
     assert_eq!(client.get_vk_hash(), Some(vk_hash));
 }
 
 #[test]
-#[should_panic]
-fn reject_bad_count() {
+fn reject_wrong_public_input() {
     let env = Env::default();
-    env.mock_all_auths();
+    let vk = build_vk(&env);
 
-    let admin = Address::generate(&env);
-    let contract_id = env.register(ZkVerifierContract, (admin.clone(),));
-    let client = ZkVerifierContractClient::new(&env, &contract_id);
+    let pi_hash = turn_public_input(&env);
+    let mut proof = build_valid_proof(&env, &pi_hash);
+    // Flip the low byte of pi_hash: still canonical, but not what was proven.
+    proof.set(35, proof.get(35).unwrap() ^ 1);
 
-    let vk = Bytes::from_array(&env, b"vk-test");
+    assert_eq!(
+        verify_groth16(&env, &parse_vk(&env, &vk).unwrap(), &proof),
+        Err(VerifierError::PairingFailed)
+    );
+}
+
+#[test]
+fn reject_public_input_shifted_by_modulus() {
+    let env = Env::default();
+    let client = setup(&env);
+    let vk = build_vk(&env);
     client.set_vk(&vk);
 
-    let pi_hash: BytesN<32> = env.crypto().keccak256(&Bytes::from_array(&env, b"turn-public")).into();
+    let pi_hash = turn_public_input(&env);
+    let proof = build_valid_proof(&env, &pi_hash);
+
+    // Same A and C, public input re-encoded as pi_hash + r. Fr::from_bytes
+    // would reduce it back to pi_hash and the pairing would still hold.
+    let mut malleated = proof.slice(0..4);
+    malleated.append(&plus_modulus(&env, &pi_hash).into());
+    malleated.append(&proof.slice(36..proof.len()));
+
+    assert_eq!(
+        verify_groth16(&env, &parse_vk(&env, &vk).unwrap(), &malleated),
+        Err(VerifierError::NonCanonicalInput)
+    );
+    assert_eq!(
+        client.try_verify_proof_with_stored_vk(&malleated),
+        Err(Ok(VerifierError::NonCanonicalInput))
+    );
+    assert!(client.try_verify_proof_with_stored_vk(&proof).is_ok());
+}
+
+#[test]
+fn reject_public_input_equal_to_modulus() {
+    let env = Env::default();
+    let vk = build_vk(&env);
+
+    // r itself reduces to 0; prove for 0, then present it as r.
+    let zero = BytesN::from_array(&env, &[0u8; 32]);
+    let proof = build_valid_proof(&env, &zero);
+    assert_eq!(verify_groth16(&env, &parse_vk(&env, &vk).unwrap(), &proof), Ok(()));
+
+    let mut malleated = proof.slice(0..4);
+    malleated.append(&plus_modulus(&env, &zero).into());
+    malleated.append(&proof.slice(36..proof.len()));
+
+    assert_eq!(
+        verify_groth16(&env, &parse_vk(&env, &vk).unwrap(), &malleated),
+        Err(VerifierError::NonCanonicalInput)
+    );
+}
+
+#[test]
+fn reject_bad_count() {
+    let env = Env::default();
+    let client = setup(&env);
+    client.set_vk(&build_vk(&env));
+
+    let pi_hash = turn_public_input(&env);
     let mut proof = build_valid_proof(&env, &pi_hash);
     // Set count = 2 instead of 1
     proof.set(3, 2u8);
 
-    let _ = client.verify_proof_with_stored_vk(&proof);
+    assert_eq!(
+        client.try_verify_proof_with_stored_vk(&proof),
+        Err(Ok(VerifierError::InputCountMismatch))
+    );
 }
 
 #[test]
-#[should_panic]
 fn reject_too_short() {
     let env = Env::default();
-    env.mock_all_auths();
-
-    let admin = Address::generate(&env);
-    let contract_id = env.register(ZkVerifierContract, (admin.clone(),));
-    let client = ZkVerifierContractClient::new(&env, &contract_id);
-
-    let vk = Bytes::from_array(&env, b"vk-test");
-    client.set_vk(&vk);
+    let client = setup(&env);
+    client.set_vk(&build_vk(&env));
 
     // Only 10 bytes — too short
     let proof = Bytes::from_array(&env, b"tooshort!!");
-    let _ = client.verify_proof_with_stored_vk(&proof);
+    assert_eq!(
+        client.try_verify_proof_with_stored_vk(&proof),
+        Err(Ok(VerifierError::InputCountMismatch))
+    );
+
+    // Right count, truncated before pi_c.
+    let pi_hash = turn_public_input(&env);
+    let full = build_valid_proof(&env, &pi_hash);
+    assert_eq!(
+        client.try_verify_proof_with_stored_vk(&full.slice(0..full.len() - 1)),
+        Err(Ok(VerifierError::MalformedProof))
+    );
 }
 
 #[test]
-#[should_panic]
-fn reject_without_vk() {
+fn reject_trailing_bytes() {
     let env = Env::default();
-    env.mock_all_auths();
+    let client = setup(&env);
+    let vk = build_vk(&env);
+    let vk_id = client.set_vk(&vk);
+
+    let proof = build_valid_proof(&env, &turn_public_input(&env));
+    let mut padded = proof.clone();
+    padded.push_back(0);
+    assert_eq!(
+        verify_groth16(&env, &parse_vk(&env, &vk).unwrap(), &padded),
+        Err(VerifierError::MalformedProof)
+    );
+    assert_eq!(
+        client.try_verify_proof(&vk_id, &padded),
+        Err(Ok(VerifierError::MalformedProof))
+    );
+    assert_eq!(
+        client.try_verify_batch(&vk_id, &vec![&env, proof.clone(), padded]),
+        Err(Ok(VerifierError::MalformedProof))
+    );
+
+    // The id covers the inputs and points only, not the blob as sent.
+    assert_eq!(client.verify_proof(&vk_id, &proof), expected_proof_id(&env, &proof));
+}
 
-    let admin = Address::generate(&env);
-    let contract_id = env.register(ZkVerifierContract, (admin.clone(),));
-    let client = ZkVerifierContractClient::new(&env, &contract_id);
+#[test]
+fn reject_without_vk() {
+    let env = Env::default();
+    let client = setup(&env);
 
-    // No set_vk call — should panic with "vk not set"
-    let pi_hash: BytesN<32> = env.crypto().keccak256(&Bytes::from_array(&env, b"test")).into();
+    // No set_vk call
+    let pi_hash = turn_public_input(&env);
     let proof = build_valid_proof(&env, &pi_hash);
-    let _ = client.verify_proof_with_stored_vk(&proof);
+    assert_eq!(
+        client.try_verify_proof_with_stored_vk(&proof),
+        Err(Ok(VerifierError::VkNotSet))
+    );
+}
+
+#[test]
+fn registered_keys_verify_side_by_side_until_retired() {
+    let env = Env::default();
+    let client = setup(&env);
+
+    let old_id = client.set_vk(&build_vk(&env));
+    let new_id = client.register_vk(&build_vk_with_alpha(&env, ALPHA + 1));
+    assert_ne!(old_id, new_id);
+    assert!(client.is_vk_registered(&new_id));
+    // Registering does not move the default.
+    assert_eq!(client.get_vk_hash(), Some(old_id.clone()));
+
+    let pi_hash = turn_public_input(&env);
+    let old_proof = build_valid_proof(&env, &pi_hash);
+    let new_proof = build_valid_proof_with_alpha(&env, ALPHA + 1, &pi_hash);
+
+    client.verify_proof(&old_id, &old_proof);
+    client.verify_proof(&new_id, &new_proof);
+    client.verify_proof_with_stored_vk(&old_proof);
+    assert_eq!(
+        client.try_verify_proof(&new_id, &old_proof),
+        Err(Ok(VerifierError::PairingFailed))
+    );
+
+    // Rolling the default forward leaves the old key usable by id.
+    client.set_vk(&build_vk_with_alpha(&env, ALPHA + 1));
+    assert_eq!(client.get_vk_hash(), Some(new_id.clone()));
+    client.verify_proof(&old_id, &old_proof);
+
+    client.retire_vk(&old_id);
+    assert!(!client.is_vk_registered(&old_id));
+    assert_eq!(
+        client.try_verify_proof(&old_id, &old_proof),
+        Err(Ok(VerifierError::VkNotSet))
+    );
+
+    // Retiring the default clears it.
+    client.retire_vk(&new_id);
+    assert_eq!(client.get_vk_hash(), None);
+    assert_eq!(
+        client.try_verify_proof_with_stored_vk(&new_proof),
+        Err(Ok(VerifierError::VkNotSet))
+    );
+}
+
+/// A verifier whose VK was set by the first release: raw bytes under the
+/// instance key "vk", plus its hash as the default id.
+fn setup_with_legacy_vk(env: &Env) -> (ZkVerifierContractClient<'_>, BytesN<32>) {
+    let client = setup(env);
+    let vk = build_vk(env);
+    let vk_id: BytesN<32> = env.crypto().sha256(&vk).into();
+    env.as_contract(&client.address, || {
+        env.storage().instance().set(&crate::key_legacy_vk(), &vk);
+        env.storage().instance().set(&crate::key_vk_hash(), &vk_id);
+    });
+    (client, vk_id)
+}
+
+#[test]
+fn legacy_vk_is_moved_into_the_registry_on_first_use() {
+    let env = Env::default();
+    let proof = build_valid_proof(&env, &turn_public_input(&env));
+    let legacy_vk_left = |client: &ZkVerifierContractClient| {
+        env.as_contract(&client.address, || env.storage().instance().has(&crate::key_legacy_vk()))
+    };
+
+    // Through the default id, as games started before the upgrade do.
+    let (client, vk_id) = setup_with_legacy_vk(&env);
+    assert_eq!(client.get_vk_hash(), Some(vk_id.clone()));
+    assert!(client.is_vk_registered(&vk_id));
+    client.verify_proof_with_stored_vk(&proof);
+    assert!(!legacy_vk_left(&client));
+    client.verify_proof(&vk_id, &proof);
+
+    // Through an explicit id.
+    let (client, vk_id) = setup_with_legacy_vk(&env);
+    client.verify_proof(&vk_id, &proof);
+    assert!(!legacy_vk_left(&client));
+
+    // Replacing the default keeps the legacy key usable by id.
+    let (client, vk_id) = setup_with_legacy_vk(&env);
+    let new_id = client.set_vk(&build_vk_with_alpha(&env, ALPHA + 1));
+    assert!(!legacy_vk_left(&client));
+    assert_eq!(client.get_vk_hash(), Some(new_id));
+    client.verify_proof(&vk_id, &proof);
+
+    // Retiring it does not bring it back from instance storage.
+    let (client, vk_id) = setup_with_legacy_vk(&env);
+    client.retire_vk(&vk_id);
+    assert!(!client.is_vk_registered(&vk_id));
+    assert_eq!(client.get_vk_hash(), None);
+    assert_eq!(
+        client.try_verify_proof(&vk_id, &proof),
+        Err(Ok(VerifierError::VkNotSet))
+    );
+}
+
+#[test]
+fn set_vk_rejects_malformed_keys() {
+    let env = Env::default();
+    let client = setup(&env);
+    let vk = build_vk(&env);
+
+    let mut padded = vk.clone();
+    padded.push_back(0);
+    let mut no_ic = vk.slice(0..448);
+    no_ic.extend_from_array(&0u32.to_be_bytes());
+    for malformed in [vk.slice(0..vk.len() - 1), padded, no_ic] {
+        assert_eq!(client.try_set_vk(&malformed), Err(Ok(VerifierError::MalformedVk)));
+    }
+
+    // beta's first coordinate with the flag bits set (≥ p).
+    let mut flagged = vk.clone();
+    flagged.set(64, 0xc0);
+    // IC[1].y = p exactly.
+    let mut ic_at_modulus = vk.slice(0..vk.len() - 32);
+    ic_at_modulus.extend_from_array(&BN254_FP_MODULUS);
+    // alpha at infinity.
+    let mut no_alpha = Bytes::from_array(&env, &[0u8; 64]);
+    no_alpha.append(&vk.slice(64..vk.len()));
+    for invalid in [flagged, ic_at_modulus, no_alpha] {
+        assert_eq!(client.try_register_vk(&invalid), Err(Ok(VerifierError::InvalidVkPoint)));
+    }
+
+    assert_eq!(client.get_vk_hash(), None);
+}
+
+#[test]
+fn set_vk_rejects_points_off_the_curve() {
+    let env = Env::default();
+    let client = setup(&env);
+    let vk = build_vk(&env);
+
+    // (1, 3) is not on y² = x³ + 3.
+    let mut off_curve_ic = vk.slice(0..vk.len() - 1);
+    off_curve_ic.push_back(3);
+    let mut off_curve_alpha = vk.clone();
+    off_curve_alpha.set(63, vk.get(63).unwrap() ^ 1);
+    let mut off_curve_beta = vk.clone();
+    off_curve_beta.set(191, G2_GEN[127] ^ 1);
+    let mut off_curve_delta = vk.clone();
+    off_curve_delta.set(320 + 127, G2_GEN[127] ^ 1);
+    let mut off_subgroup_gamma = vk.slice(0..192);
+    off_subgroup_gamma.append(&Bytes::from_array(&env, &G2_OFF_SUBGROUP));
+    off_subgroup_gamma.append(&vk.slice(320..vk.len()));
+
+    for invalid in [off_curve_ic, off_curve_alpha, off_curve_beta, off_curve_delta, off_subgroup_gamma] {
+        assert_eq!(client.try_set_vk(&invalid), Err(Ok(VerifierError::InvalidVkPoint)));
+        assert_eq!(client.try_register_vk(&invalid), Err(Ok(VerifierError::InvalidVkPoint)));
+    }
+    assert_eq!(client.get_vk_hash(), None);
+    assert!(client.try_set_vk(&vk).is_ok());
+}
+
+#[test]
+fn keys_are_refused_until_a_point_checker_is_set() {
+    let env = Env::default();
+    let client = setup_without_point_checker(&env);
+    let vk = build_vk(&env);
+
+    assert_eq!(client.try_set_vk(&vk), Err(Ok(VerifierError::PointCheckerNotSet)));
+    assert_eq!(client.get_vk_hash(), None);
+
+    client.set_point_checker(&env.register(ZkVerifierContract, (Address::generate(&env),)));
+    assert!(client.try_set_vk(&vk).is_ok());
+}
+
+#[test]
+fn batch_verifies_valid_proofs_and_returns_their_ids() {
+    let env = Env::default();
+    let client = setup(&env);
+    let vk_id = client.set_vk(&build_vk(&env));
+
+    let proofs = valid_proofs(&env, 4);
+    let proof_ids = client.verify_batch(&vk_id, &proofs);
+
+    assert_eq!(proof_ids.len(), 4);
+    for (proof, proof_id) in proofs.iter().zip(proof_ids.iter()) {
+        assert_eq!(proof_id, expected_proof_id(&env, &proof));
+        assert!(!client.is_verified(&proof_id));
+    }
+    assert_eq!(client.verify_batch(&vk_id, &Vec::new(&env)).len(), 0);
+}
+
+#[test]
+fn batch_fails_as_a_whole_on_one_bad_proof() {
+    let env = Env::default();
+    let client = setup(&env);
+    let vk_id = client.set_vk(&build_vk(&env));
+
+    let mut proofs = valid_proofs(&env, 3);
+    let mut bad = proofs.get(1).unwrap();
+    bad.set(35, bad.get(35).unwrap() ^ 1);
+    proofs.set(1, bad);
+    assert_eq!(
+        client.try_verify_batch(&vk_id, &proofs),
+        Err(Ok(VerifierError::PairingFailed))
+    );
+    assert!(!client.is_verified(&expected_proof_id(&env, &proofs.get(0).unwrap())));
+
+    // Two proofs with their public inputs swapped: each equation is off by
+    // an opposite amount, which a plain (unweighted) sum would cancel out.
+    let a = proofs.get(0).unwrap();
+    let c = proofs.get(2).unwrap();
+    let mut a_swapped = a.slice(0..4);
+    a_swapped.append(&c.slice(4..36));
+    a_swapped.append(&a.slice(36..a.len()));
+    let mut c_swapped = c.slice(0..4);
+    c_swapped.append(&a.slice(4..36));
+    c_swapped.append(&c.slice(36..c.len()));
+    assert_eq!(
+        client.try_verify_batch(&vk_id, &vec![&env, a_swapped, c_swapped]),
+        Err(Ok(VerifierError::PairingFailed))
+    );
+
+    let mut malleated = a.slice(0..4);
+    malleated.append(&plus_modulus(&env, &nth_public_input(&env, 0)).into());
+    malleated.append(&a.slice(36..a.len()));
+    assert_eq!(
+        client.try_verify_batch(&vk_id, &vec![&env, c, malleated]),
+        Err(Ok(VerifierError::NonCanonicalInput))
+    );
+}
+
+#[test]
+fn batch_costs_fewer_instructions_than_single_calls() {
+    let env = Env::default();
+    let client = setup(&env);
+    let vk_id = client.set_vk(&build_vk(&env));
+    env.cost_estimate().budget().reset_unlimited();
+
+    let proofs = valid_proofs(&env, 4);
+
+    // The budget is reset before every top-level invocation.
+    let mut singles_cpu = 0u64;
+    for proof in proofs.iter() {
+        client.verify_proof(&vk_id, &proof);
+        singles_cpu += env.cost_estimate().budget().cpu_instruction_cost();
+    }
+    client.verify_batch(&vk_id, &proofs);
+    let batch_cpu = env.cost_estimate().budget().cpu_instruction_cost();
+
+    assert!(batch_cpu < singles_cpu);
+}
+
+#[test]
+fn proof_records_leave_instance_storage_constant_size() {
+    let env = Env::default();
+    let client = setup(&env);
+    let vk_id = client.set_vk(&build_vk(&env));
+    let consumer = register_consumer(&env, &client);
+    let instance_len = || {
+        env.as_contract(&client.address, || env.storage().instance().all().len())
+    };
+
+    client.consume_proof(&consumer, &None, &build_valid_proof(&env, &nth_public_input(&env, 0)));
+    let before = instance_len();
+    for proof in valid_proofs(&env, 3).iter() {
+        client.consume_proof(&consumer, &Some(vk_id.clone()), &proof);
+    }
+    client.verify_batch(&vk_id, &valid_proofs(&env, 5));
+    assert_eq!(instance_len(), before);
+
+    let proof_id = expected_proof_id(&env, &valid_proofs(&env, 1).get(0).unwrap());
+    env.as_contract(&client.address, || {
+        // Three distinct proofs, each recorded once however often it was consumed.
+        assert_eq!(env.storage().temporary().all().len(), 3);
+        assert_eq!(
+            env.storage().temporary().get_ttl(&crate::DataKey::Proof(proof_id.clone())),
+            PROOF_TTL_LEDGERS
+        );
+    });
+    assert!(client.is_verified(&proof_id));
+
+    env.ledger().with_mut(|li| li.sequence_number += PROOF_TTL_LEDGERS + 1);
+    assert!(!client.is_verified(&proof_id));
+}
+
+#[test]
+fn replays_are_rejected_only_when_enabled() {
+    let env = Env::default();
+    let client = setup(&env);
+    let vk_id = client.set_vk(&build_vk(&env));
+    let consumer = register_consumer(&env, &client);
+    let proof = build_valid_proof(&env, &turn_public_input(&env));
+
+    assert!(!client.rejects_replays());
+    let proof_id = client.consume_proof(&consumer, &Some(vk_id.clone()), &proof);
+    assert_eq!(client.consume_proof(&consumer, &None, &proof), proof_id);
+
+    client.set_reject_replays(&true);
+    assert_eq!(
+        client.try_consume_proof(&consumer, &Some(vk_id.clone()), &proof),
+        Err(Ok(VerifierError::ProofReplayed))
+    );
+    assert_eq!(
+        client.try_consume_proof(&consumer, &None, &proof),
+        Err(Ok(VerifierError::ProofReplayed))
+    );
+    // Plain verification neither records nor rejects.
+    assert_eq!(client.verify_proof(&vk_id, &proof), proof_id);
+    assert_eq!(client.verify_batch(&vk_id, &vec![&env, proof.clone(), proof.clone()]).len(), 2);
+
+    client.set_reject_replays(&false);
+    assert_eq!(client.consume_proof(&consumer, &None, &proof), proof_id);
+}
+
+#[test]
+fn only_registered_consumers_can_use_up_a_proof() {
+    let env = Env::default();
+    let client = setup(&env);
+    let vk_id = client.set_vk(&build_vk(&env));
+    client.set_reject_replays(&true);
+    let consumer = register_consumer(&env, &client);
+    let proof = build_valid_proof(&env, &turn_public_input(&env));
+
+    // Someone who saw the proof verifies it first, directly or by claiming to
+    // be a consumer; the player's own submission still goes through.
+    client.verify_proof(&vk_id, &proof);
+    client.verify_proof_with_stored_vk(&proof);
+    let stranger = Address::generate(&env);
+    assert_eq!(
+        client.try_consume_proof(&stranger, &None, &proof),
+        Err(Ok(VerifierError::UnknownConsumer))
+    );
+    let proof_id = client.consume_proof(&consumer, &None, &proof);
+    assert_eq!(env.auths()[0].0, consumer);
+    assert!(client.is_verified(&proof_id));
+
+    client.set_consumer(&consumer, &false);
+    assert!(!client.is_consumer(&consumer));
+    assert_eq!(
+        client.try_consume_proof(&consumer, &None, &valid_proofs(&env, 2).get(1).unwrap()),
+        Err(Ok(VerifierError::UnknownConsumer))
+    );
+}
+
+#[test]
+fn prune_drops_proof_records() {
+    let env = Env::default();
+    let client = setup(&env);
+    let vk_id = client.set_vk(&build_vk(&env));
+    client.set_reject_replays(&true);
+    let consumer = register_consumer(&env, &client);
+    let proof = build_valid_proof(&env, &turn_public_input(&env));
+    let proof_id = client.consume_proof(&consumer, &Some(vk_id), &proof);
+
+    // A record left in instance storage by an earlier contract version.
+    let legacy_id = BytesN::from_array(&env, &[7u8; 32]);
+    env.as_contract(&client.address, || {
+        env.storage().instance().set(&legacy_id, &true);
+    });
+    assert!(client.is_verified(&legacy_id));
+
+    client.prune(&vec![&env, proof_id.clone(), legacy_id.clone()]);
+    assert!(!client.is_verified(&proof_id));
+    assert!(!client.is_verified(&legacy_id));
+    assert_eq!(client.consume_proof(&consumer, &None, &proof), proof_id);
 }
@@ -6,7 +6,9 @@ import {
   computePosCommit,
   deriveNewPosNonce,
   computeStateCommitment,
+  computeConfigHash,
   computeTurnPiHash,
+  computeTurnPiHashV2,
   generateMap,
   computeLootDelta,
   computeCameraHits,
@@ -14,6 +16,8 @@ import {
   zeroBitset,
   CAMERA_PENALTY,
   LASER_PENALTY,
+  PI_SCHEMA_V1,
+  PI_SCHEMA_V2,
   type Position,
   type PlayerGameView,
   type TurnPublic,
@@ -289,24 +293,43 @@ export async function buildTurn(
     newP1PosCommit,
     newP2PosCommit,
     sessionSeedBytes,
+    computeConfigHash(view.config),
   );
 
   // ─── pi_hash (Groth16 public input) ─────────────────────────────────────────
-  // Poseidon2(
-  //   Poseidon4(session_id, turn_index, player_tag, pos_commit_before),
-  //   Poseidon5(pos_commit_after, score_delta_fr, loot_delta, no_path_flag, exited_flag)
-  // )
-  const piHashBytes = computeTurnPiHash(
-    sessionId,
-    view.turnIndex,
-    playerTag,
-    posCommitBefore,
-    posCommitAfter,
-    scoreDelta,
-    lootItems,
-    noPathFlag,
-    exitedFlag,
-  );
+  // h1 = Poseidon4(session_id, turn_index, player_tag, pos_commit_before)
+  // h2 = Poseidon5(pos_commit_after, score_delta_fr, loot_delta, no_path_flag, exited_flag)
+  // v1: Poseidon2(h1, h2)    v2: Poseidon3(h1, h2, loot_mask)
+  // Later schemas need public inputs this client doesn't produce yet.
+  let piHashBytes: Uint8Array;
+  if (view.piSchema === PI_SCHEMA_V1) {
+    piHashBytes = computeTurnPiHash(
+      sessionId,
+      view.turnIndex,
+      playerTag,
+      posCommitBefore,
+      posCommitAfter,
+      scoreDelta,
+      lootItems,
+      noPathFlag,
+      exitedFlag,
+    );
+  } else if (view.piSchema === PI_SCHEMA_V2) {
+    piHashBytes = computeTurnPiHashV2(
+      sessionId,
+      view.turnIndex,
+      playerTag,
+      posCommitBefore,
+      posCommitAfter,
+      scoreDelta,
+      lootItems,
+      lootDelta,
+      noPathFlag,
+      exitedFlag,
+    );
+  } else {
+    throw new Error(`Unsupported turn public-input schema v${view.piSchema} for this game.`);
+  }
 
   // ─── Groth16 circuit inputs ─────────────────────────────────────────────────
 
@@ -347,6 +370,7 @@ export async function buildTurn(
     lootDelta:  lootItems,
     noPathFlag: noPathFlag ? 1 : 0,
     exitedFlag: exitedFlag ? 1 : 0,
+    piSchema:   view.piSchema,
     // Optional hints for backend logging
     posCommitBefore: bytesToHex(posCommitBefore),
     posCommitAfter:  bytesToHex(posCommitAfter),
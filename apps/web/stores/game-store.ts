@@ -1,5 +1,5 @@
 import { create } from 'zustand';
-import { BITSET_BYTES, HeistContractClient, generateMap, zeroBitset, type GameView, type PlayerGameView } from '@repo/stellar';
+import { BITSET_BYTES, DEFAULT_GAME_CONFIG, HeistContractClient, generateMap, zeroBitset, type GameConfig, type GameView, type PlayerGameView } from '@repo/stellar';
 import type { TurnBreakdown } from '../lib/turn-builder';
 import { getRuntimeConfig } from '../lib/runtime-config';
 import { usePrivateStore } from './private-store';
@@ -108,6 +108,8 @@ function deserializeGameView(raw: unknown): GameView {
     player1Exited:      Boolean(r.player1Exited),
     player2Exited:      Boolean(r.player2Exited),
     lootCollectedMask:  r.lootCollectedMask != null ? toUint8Array(r.lootCollectedMask) : new Uint8Array(18),
+    piSchema:           r.piSchema != null ? Number(r.piSchema) : 1,
+    config:             r.config != null ? (r.config as GameConfig) : DEFAULT_GAME_CONFIG,
   };
 }
 
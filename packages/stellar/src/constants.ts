@@ -1,4 +1,5 @@
 import { Networks } from "@stellar/stellar-sdk";
+import type { GameConfig } from "./types";
 
 export const STELLAR_NETWORK = "testnet";
 export const NETWORK_PASSPHRASE = Networks.TESTNET;
@@ -20,3 +21,17 @@ export const BITSET_BYTES = 18;
 export const GAME_SECONDS = 300;
 export const CAMERA_PENALTY = 1n;
 export const LASER_PENALTY = 2n;
+
+export const CLOCK_MODE_FISCHER = 0;
+export const CLOCK_MODE_BRONSTEIN = 1;
+
+/** The rules games were played under before GameConfig existed. */
+export const DEFAULT_GAME_CONFIG: GameConfig = {
+  timePerPlayer: GAME_SECONDS,
+  clockMode: CLOCK_MODE_FISCHER,
+  incrementSeconds: 0,
+  maxTurns: 0,
+  lootCount: 24,
+  boardWidth: MAP_W,
+  boardHeight: MAP_H,
+};
@@ -8,9 +8,9 @@ import {
   scValToNative,
   Keypair,
 } from "@stellar/stellar-sdk";
-import type { TurnZkPublic, GameView } from "./types";
+import type { TurnZkPublic, GameView, GameConfig } from "./types";
 export type { GameView } from "./types";
-import { NETWORK_PASSPHRASE } from "./constants";
+import { DEFAULT_GAME_CONFIG, NETWORK_PASSPHRASE } from "./constants";
 
 /**
  * Information about a Soroban auth entry that needs a player's signature.
@@ -34,6 +34,10 @@ function u32Val(v: number): xdr.ScVal {
   return nativeToScVal(v, { type: "u32" });
 }
 
+function u64Val(v: number): xdr.ScVal {
+  return nativeToScVal(BigInt(v), { type: "u64" });
+}
+
 function i128Val(v: bigint): xdr.ScVal {
   return nativeToScVal(v, { type: "i128" });
 }
@@ -140,6 +144,43 @@ function turnZkPublicVal(turn: TurnZkPublic): xdr.ScVal {
   ]);
 }
 
+/**
+ * Encode a GameConfig as a Soroban ScvMap (fields in alphabetical order).
+ * clock_mode is an integer enum, so it travels as a plain u32.
+ */
+function gameConfigVal(config: GameConfig): xdr.ScVal {
+  return xdr.ScVal.scvMap([
+    new xdr.ScMapEntry({
+      key: xdr.ScVal.scvSymbol("board_height"),
+      val: u32Val(config.boardHeight),
+    }),
+    new xdr.ScMapEntry({
+      key: xdr.ScVal.scvSymbol("board_width"),
+      val: u32Val(config.boardWidth),
+    }),
+    new xdr.ScMapEntry({
+      key: xdr.ScVal.scvSymbol("clock_mode"),
+      val: u32Val(config.clockMode),
+    }),
+    new xdr.ScMapEntry({
+      key: xdr.ScVal.scvSymbol("increment_seconds"),
+      val: u64Val(config.incrementSeconds),
+    }),
+    new xdr.ScMapEntry({
+      key: xdr.ScVal.scvSymbol("loot_count"),
+      val: u32Val(config.lootCount),
+    }),
+    new xdr.ScMapEntry({
+      key: xdr.ScVal.scvSymbol("max_turns"),
+      val: u32Val(config.maxTurns),
+    }),
+    new xdr.ScMapEntry({
+      key: xdr.ScVal.scvSymbol("time_per_player"),
+      val: u64Val(config.timePerPlayer),
+    }),
+  ]);
+}
+
 /* ------------------------------------------------------------------ */
 /*  ScVal decoding helpers                                             */
 /* ------------------------------------------------------------------ */
@@ -165,6 +206,19 @@ function parseGameStatus(val: xdr.ScVal): string {
   return scValToNative(val);
 }
 
+function parseGameConfig(val: xdr.ScVal): GameConfig {
+  const c = scValToNative(val) as Record<string, number | bigint>;
+  return {
+    timePerPlayer: Number(c["time_per_player"]),
+    clockMode: Number(c["clock_mode"]),
+    incrementSeconds: Number(c["increment_seconds"]),
+    maxTurns: Number(c["max_turns"]),
+    lootCount: Number(c["loot_count"]),
+    boardWidth: Number(c["board_width"]),
+    boardHeight: Number(c["board_height"]),
+  };
+}
+
 function parseGameView(resultVal: xdr.ScVal): GameView {
   const map = resultVal.map();
   if (!map) throw new Error("Expected map for GameView");
@@ -205,6 +259,8 @@ function parseGameView(resultVal: xdr.ScVal): GameView {
     lootCollectedMask: i128ToLootMask(
       BigInt(scValToNative(view["loot_mask"] as xdr.ScVal) ?? 0n),
     ),
+    piSchema: Number(scValToNative(view["pi_schema"] as xdr.ScVal) ?? 1),
+    config: view["config"] ? parseGameConfig(view["config"] as xdr.ScVal) : DEFAULT_GAME_CONFIG,
   };
 }
 
@@ -395,6 +451,7 @@ export class HeistContractClient {
    *  - seedCommit: for dice randomness (revealed later via reveal_seed)
    *  - mapSeedCommit: for map generation (secret never revealed on-chain;
    *    relayed off-chain via backend after both seeds are revealed)
+   * config fixes the game's rules (clock, turn limit, loot, board size).
    */
   async buildStartGameTx(
     sourceAddress: string,
@@ -407,6 +464,7 @@ export class HeistContractClient {
     p2SeedCommit: Uint8Array,
     p1MapSeedCommit: Uint8Array,
     p2MapSeedCommit: Uint8Array,
+    config: GameConfig = DEFAULT_GAME_CONFIG,
   ): Promise<{ txXdr: string; authInfos: AuthEntryInfo[] }> {
     const account = await this.server.getAccount(sourceAddress);
     const tx = new TransactionBuilder(account, {
@@ -425,6 +483,7 @@ export class HeistContractClient {
           bytesNVal(p2SeedCommit),
           bytesNVal(p1MapSeedCommit),
           bytesNVal(p2MapSeedCommit),
+          gameConfigVal(config),
         ),
       )
       .setTimeout(300)
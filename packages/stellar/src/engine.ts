@@ -9,7 +9,7 @@ import {
   CAMERA_PENALTY,
   LASER_PENALTY,
 } from "./constants";
-import type { Position } from "./types";
+import type { GameConfig, Position } from "./types";
 
 // BN254 scalar field prime
 const BN254_FR_PRIME =
@@ -131,6 +131,14 @@ function writeU32BE(out: Uint8Array, offset: number, v: number): void {
   out[offset + 3] = v & 0xff;
 }
 
+function writeU64BE(out: Uint8Array, offset: number, v: number): void {
+  let bits = BigInt(v);
+  for (let i = 7; i >= 0; i--) {
+    out[offset + i] = Number(bits & 0xffn);
+    bits >>= 8n;
+  }
+}
+
 /** Write a signed i128 as 16 bytes big-endian (two's complement). */
 function writeI128BE(out: Uint8Array, offset: number, v: bigint): void {
   let bits = v < 0n ? v + (1n << 128n) : v;
@@ -435,6 +443,7 @@ export function deriveNewPosNonce(_posNonce: Uint8Array, _turnIndex: number): Ui
  * State commitment over all on-chain committed values.
  * Mirrors compute_state_commitment in engine.rs.
  * Per-player chess clocks replace the global deadline — no deadlineTs included.
+ * configHash is computeConfigHash() of the game's config, appended last.
  */
 export function computeStateCommitment(
   sessionId: number,
@@ -445,8 +454,9 @@ export function computeStateCommitment(
   player1PosCommit: Uint8Array,
   player2PosCommit: Uint8Array,
   sessionSeed: Uint8Array,
+  configHash: Uint8Array,
 ): Uint8Array {
-  const out = new Uint8Array(4 + 4 + 16 + 16 + 32 + 32 + 32 + 32);
+  const out = new Uint8Array(4 + 4 + 16 + 16 + 32 + 32 + 32 + 32 + 32);
   let off = 0;
   writeU32BE(out, off, sessionId); off += 4;
   writeU32BE(out, off, turnIndex); off += 4;
@@ -455,7 +465,26 @@ export function computeStateCommitment(
   out.set(mapCommitment, off); off += 32;
   out.set(player1PosCommit, off); off += 32;
   out.set(player2PosCommit, off); off += 32;
-  out.set(sessionSeed, off);
+  out.set(sessionSeed, off); off += 32;
+  out.set(configHash, off);
+  return keccak256(out);
+}
+
+/**
+ * Commitment to a game's rules. Mirrors compute_config_hash in engine.rs:
+ * keccak256(time_per_player ‖ clock_mode ‖ increment ‖ max_turns ‖ loot_count ‖ width ‖ height),
+ * with u64 fields as 8 bytes and u32 fields as 4 bytes, big-endian.
+ */
+export function computeConfigHash(config: GameConfig): Uint8Array {
+  const out = new Uint8Array(8 + 4 + 8 + 4 + 4 + 4 + 4);
+  let off = 0;
+  writeU64BE(out, off, config.timePerPlayer); off += 8;
+  writeU32BE(out, off, config.clockMode); off += 4;
+  writeU64BE(out, off, config.incrementSeconds); off += 8;
+  writeU32BE(out, off, config.maxTurns); off += 4;
+  writeU32BE(out, off, config.lootCount); off += 4;
+  writeU32BE(out, off, config.boardWidth); off += 4;
+  writeU32BE(out, off, config.boardHeight);
   return keccak256(out);
 }
 
@@ -484,8 +513,13 @@ export function rollValue(
 /*  Turn public-input hash                                             */
 /* ------------------------------------------------------------------ */
 
+/** Turn public-input schema the original circuit proves: Poseidon2(h1, h2). */
+export const PI_SCHEMA_V1 = 1;
+/** Schema v1 plus the turn's loot mask: Poseidon3(h1, h2, loot_mask). */
+export const PI_SCHEMA_V2 = 2;
+
 /**
- * Compute the single Groth16 ZK public input hash for a turn.
+ * Compute the single Groth16 ZK public input hash for a turn (schema v1).
  *
  * Formula (mirrors compute_turn_pi_hash() in engine.rs and the Circom circuit):
  *   h1      = Poseidon4(session_id, turn_index, player_tag, pos_commit_before_fr)
@@ -506,14 +540,70 @@ export function computeTurnPiHash(
   noPathFlag: boolean,
   exitedFlag: boolean,
 ): Uint8Array {
+  const [h1, h2] = turnPiHalves(
+    sessionId, turnIndex, playerTag, posCommitBefore, posCommitAfter,
+    scoreDelta, lootDelta, noPathFlag, exitedFlag,
+  );
+  return fieldToBytes32(poseidon2([h1, h2]));
+}
+
+/**
+ * Compute the turn public input hash for schema v2, which also binds the
+ * 18-byte loot mask of the cells collected this turn.
+ *
+ * Formula (mirrors compute_turn_pi_hash_v2() in engine.rs and TurnValidity(2)):
+ *   pi_hash = Poseidon3(h1, h2, loot_mask)
+ * where loot_mask is the bitset read as a little-endian integer (cell i → bit i).
+ */
+export function computeTurnPiHashV2(
+  sessionId: number,
+  turnIndex: number,
+  playerTag: number,
+  posCommitBefore: Uint8Array,
+  posCommitAfter: Uint8Array,
+  scoreDelta: bigint,
+  lootDelta: number,
+  lootMask: Uint8Array,
+  noPathFlag: boolean,
+  exitedFlag: boolean,
+): Uint8Array {
+  const [h1, h2] = turnPiHalves(
+    sessionId, turnIndex, playerTag, posCommitBefore, posCommitAfter,
+    scoreDelta, lootDelta, noPathFlag, exitedFlag,
+  );
+  return fieldToBytes32(poseidon3([h1, h2, lootMaskToField(lootMask)]));
+}
+
+function turnPiHalves(
+  sessionId: number,
+  turnIndex: number,
+  playerTag: number,
+  posCommitBefore: Uint8Array,
+  posCommitAfter: Uint8Array,
+  scoreDelta: bigint,
+  lootDelta: number,
+  noPathFlag: boolean,
+  exitedFlag: boolean,
+): [bigint, bigint] {
   const pcb = bytes32ToField(posCommitBefore);
   const pca = bytes32ToField(posCommitAfter);
   const sd  = intToField(scoreDelta);
 
   const h1 = poseidon4([BigInt(sessionId), BigInt(turnIndex), BigInt(playerTag), pcb]);
   const h2 = poseidon5([pca, sd, BigInt(lootDelta), BigInt(noPathFlag ? 1 : 0), BigInt(exitedFlag ? 1 : 0)]);
-  const pi = poseidon2([h1, h2]);
-  return fieldToBytes32(pi);
+  return [h1, h2];
+}
+
+/**
+ * Read an 18-byte loot bitset as the integer the contract stores in its i128
+ * loot mask (cells 0-126; bit 127 is the i128 sign bit and always clear).
+ */
+function lootMaskToField(bits: Uint8Array): bigint {
+  let v = 0n;
+  for (let i = 0; i < 16; i++) {
+    v |= BigInt(bits[i] ?? 0) << BigInt(i * 8);
+  }
+  return v & ((1n << 127n) - 1n);
 }
 
 /* ------------------------------------------------------------------ */
@@ -75,6 +75,21 @@ export interface TurnPublic extends TurnZkPublic {
 
 export type GameStatus = "WaitingReveal" | "Active" | "Ended";
 
+/** Per-game rules fixed at start_game (mirrors GameConfig in the heist contract). */
+export interface GameConfig {
+  /** Chess clock budget per player, in seconds. */
+  timePerPlayer: number;
+  /** CLOCK_MODE_FISCHER or CLOCK_MODE_BRONSTEIN. */
+  clockMode: number;
+  /** Credited to a player's clock after each accepted turn, per clockMode. */
+  incrementSeconds: number;
+  /** 0 = no turn limit. */
+  maxTurns: number;
+  lootCount: number;
+  boardWidth: number;
+  boardHeight: number;
+}
+
 /** ZK-private turn data. Only public outputs are revealed on-chain. */
 export interface TurnZkPublic {
   sessionId: number;
@@ -136,4 +151,8 @@ export interface GameView {
    * Derived from the on-chain loot_collected_mask updated each turn.
    */
   lootCollectedMask: Uint8Array;
+  /** Turn public-input schema pinned for this game (selects the pi_hash layout). */
+  piSchema: number;
+  /** Rules the game was started with; hashed into every state commitment. */
+  config: GameConfig;
 }